@@ -0,0 +1,207 @@
+// Chunks shorter than this are not worth a request of their own
+const MIN_CHUNK_CHARS: usize = 200;
+
+// Sentence-final punctuation, including the full-width forms of CJK text
+const SENTENCE_ENDS: &[char] = &['.', '!', '?', '…', '。', '！', '？'];
+
+/// A piece of a long text and the whitespace that followed it, so translated pieces can be
+/// joined again with the original paragraph and sentence breaks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextChunk {
+    pub text: String,
+    pub separator: String,
+}
+
+/// Longest text whose translation still fits in `max_output_tokens`. A token is about four
+/// characters of English but only one or two of CJK text, translations often run longer than
+/// their source, and the JSON reply takes a share, so this assumes 1.5 characters per token.
+pub fn chars_for_output_tokens(max_output_tokens: u32) -> usize {
+    (max_output_tokens as usize * 3 / 2).max(MIN_CHUNK_CHARS)
+}
+
+/// Splits `text` into chunks of at most `max_chars` characters. Paragraphs stay together
+/// where they fit; longer ones are split between sentences, and sentences longer than a chunk
+/// between words.
+pub fn split_text(text: &str, max_chars: usize) -> Vec<TextChunk> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut current: Option<TextChunk> = None;
+
+    for (paragraph, separator) in split_at_whitespace(text, |_, run| run.matches('\n').count() >= 2)
+    {
+        if length(&paragraph) <= max_chars {
+            push_piece(&mut chunks, &mut current, paragraph, separator, max_chars);
+            continue;
+        }
+
+        let sentences = split_at_whitespace(&paragraph, |piece, run| {
+            run.contains('\n') || piece.ends_with(SENTENCE_ENDS)
+        });
+        let last = sentences.len() - 1;
+        for (i, (sentence, sentence_separator)) in sentences.into_iter().enumerate() {
+            // The paragraph break follows the paragraph's last sentence
+            let sentence_separator = if i == last {
+                separator.clone()
+            } else {
+                sentence_separator
+            };
+            for (piece, piece_separator) in split_sentence(&sentence, sentence_separator, max_chars)
+            {
+                push_piece(&mut chunks, &mut current, piece, piece_separator, max_chars);
+            }
+        }
+    }
+
+    chunks.extend(current);
+    chunks
+}
+
+/// Splits a sentence that is longer than a chunk between words, and words that are still too
+/// long (CJK text has no spaces) after a sentence end or at the chunk size.
+fn split_sentence(sentence: &str, separator: String, max_chars: usize) -> Vec<(String, String)> {
+    if length(sentence) <= max_chars {
+        return vec![(sentence.to_string(), separator)];
+    }
+
+    let words = split_at_whitespace(sentence, |_, _| true);
+    let last = words.len() - 1;
+    let mut pieces = Vec::new();
+    for (i, (word, word_separator)) in words.into_iter().enumerate() {
+        let word_separator = if i == last {
+            separator.clone()
+        } else {
+            word_separator
+        };
+
+        let mut rest = word.as_str();
+        while length(rest) > max_chars {
+            let window: String = rest.chars().take(max_chars).collect();
+            let cut = window
+                .rfind(SENTENCE_ENDS)
+                .map(|i| i + window[i..].chars().next().map_or(1, char::len_utf8))
+                .unwrap_or(window.len());
+            pieces.push((rest[..cut].to_string(), String::new()));
+            rest = &rest[cut..];
+        }
+        pieces.push((rest.to_string(), word_separator));
+    }
+    pieces
+}
+
+/// Adds a piece to the chunk being built, or starts a new chunk when it does not fit.
+fn push_piece(
+    chunks: &mut Vec<TextChunk>,
+    current: &mut Option<TextChunk>,
+    text: String,
+    separator: String,
+    max_chars: usize,
+) {
+    match current {
+        Some(chunk)
+            if length(&chunk.text) + length(&chunk.separator) + length(&text) <= max_chars =>
+        {
+            chunk.text.push_str(&chunk.separator);
+            chunk.text.push_str(&text);
+            chunk.separator = separator;
+        }
+        _ => {
+            chunks.extend(current.take());
+            *current = Some(TextChunk { text, separator });
+        }
+    }
+}
+
+/// Splits `text` at the whitespace runs for which `is_break(preceding text, run)` holds. Each
+/// piece keeps the run that followed it as its separator; the last one keeps the trailing
+/// whitespace of `text`.
+fn split_at_whitespace(text: &str, is_break: impl Fn(&str, &str) -> bool) -> Vec<(String, String)> {
+    let text = text.trim_start();
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if !c.is_whitespace() {
+            continue;
+        }
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, next)) = chars.peek() {
+            if !next.is_whitespace() {
+                break;
+            }
+            end = j + next.len_utf8();
+            chars.next();
+        }
+
+        let piece = &text[start..i];
+        if end < text.len() && is_break(piece, &text[i..end]) {
+            pieces.push((piece.to_string(), text[i..end].to_string()));
+            start = end;
+        }
+    }
+
+    let rest = &text[start..];
+    let trimmed = rest.trim_end();
+    if !trimmed.is_empty() {
+        pieces.push((trimmed.to_string(), rest[trimmed.len()..].to_string()));
+    }
+    pieces
+}
+
+fn length(text: &str) -> usize {
+    text.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(chunks: &[TextChunk]) -> String {
+        chunks
+            .iter()
+            .map(|chunk| format!("{}{}", chunk.text, chunk.separator))
+            .collect()
+    }
+
+    #[test]
+    fn test_short_text_is_one_chunk() {
+        let chunks = split_text("Hello there.\n\nHow are you?\n", 100);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].text, "Hello there.\n\nHow are you?");
+        assert_eq!(chunks[0].separator, "\n");
+    }
+
+    #[test]
+    fn test_paragraphs_are_kept_together() {
+        let text =
+            "First paragraph, one sentence.\n\nSecond paragraph. It has two sentences.\n\nThird.";
+        let chunks = split_text(text, 45);
+        assert_eq!(
+            chunks.iter().map(|c| c.text.as_str()).collect::<Vec<_>>(),
+            [
+                "First paragraph, one sentence.",
+                "Second paragraph. It has two sentences.",
+                "Third."
+            ]
+        );
+        assert_eq!(join(&chunks), text);
+    }
+
+    #[test]
+    fn test_long_paragraph_is_split_between_sentences() {
+        let text = "One sentence here. Another sentence here! A third one? And a fourth.";
+        let chunks = split_text(text, 45);
+        assert!(chunks.iter().all(|c| length(&c.text) <= 45));
+        assert_eq!(chunks[0].text, "One sentence here. Another sentence here!");
+        assert_eq!(join(&chunks), text);
+    }
+
+    #[test]
+    fn test_text_without_spaces_is_split_after_sentence_ends() {
+        let text = "これは最初の文です。これは二番目の文です。これは三番目の文です。";
+        let chunks = split_text(text, 15);
+        assert!(chunks.iter().all(|c| length(&c.text) <= 15));
+        assert_eq!(chunks[0].text, "これは最初の文です。");
+        assert_eq!(join(&chunks), text);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::config::Config;
+use crate::translation::{
+    TranslationRequest, TranslationResult, TranslationService, choose_target_language,
+};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Outcome of one provider in a side-by-side comparison, emitted as `comparison-result`
+/// as soon as that provider finishes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparisonResult {
+    pub comparison_id: String, // Lets the UI ignore results from an earlier comparison
+    pub entry: String,         // The "provider:model" entry as requested
+    pub provider: String,
+    pub model: String,
+    pub result: Option<TranslationResult>,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+}
+
+/// Translates `text` with every "provider:model" entry concurrently. `on_result` is called
+/// as each provider finishes; the returned results keep the order of `entries`.
+pub async fn compare_translations(
+    text: &str,
+    entries: &[String],
+    comparison_id: &str,
+    config: &Config,
+    on_result: impl Fn(&ComparisonResult),
+) -> Vec<ComparisonResult> {
+    // Every provider gets the same target, so the results are comparable
+    let (_, target_language) = choose_target_language(text, config);
+    let mut request = TranslationRequest::translate(text, config);
+    request.target_language = target_language;
+
+    let comparisons = entries.iter().map(|entry| {
+        let request = &request;
+        let on_result = &on_result;
+        async move {
+            let started = Instant::now();
+            let (provider, model, outcome) = match config.parse_provider_model(entry) {
+                Some((provider, model)) => {
+                    let mut candidate = config.clone();
+                    candidate.api_provider = provider.clone();
+                    candidate.model = model.clone();
+                    let outcome = TranslationService::new(candidate)
+                        .detect_and_translate(request, None, None)
+                        .await;
+                    (provider, model, outcome)
+                }
+                None => (
+                    entry.clone(),
+                    String::new(),
+                    Err(anyhow::anyhow!("Unknown provider or model '{}'", entry)),
+                ),
+            };
+
+            let latency_ms = started.elapsed().as_millis() as u64;
+            log::info!(
+                "Comparison with {} ({}) finished in {} ms: {}",
+                provider,
+                model,
+                latency_ms,
+                if outcome.is_ok() { "ok" } else { "failed" }
+            );
+
+            let (result, error) = match outcome {
+                Ok(result) => (Some(result), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            let comparison = ComparisonResult {
+                comparison_id: comparison_id.to_string(),
+                entry: entry.clone(),
+                provider,
+                model,
+                result,
+                error,
+                latency_ms,
+            };
+            on_result(&comparison);
+            comparison
+        }
+    });
+
+    join_all(comparisons).await
+}
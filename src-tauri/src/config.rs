@@ -1,21 +1,35 @@
+use crate::languages;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct ModelConfig {
     pub name: String,
     pub display_name: String,
     pub provider: String,
     pub is_enabled: bool,
     pub description: Option<String>,
+    // Generation settings; unset ones use the provider's defaults
+    pub temperature: Option<f32>,
+    pub max_output_tokens: Option<u32>,   // Ollama: num_predict
+    pub context_window: Option<u32>,      // Ollama: num_ctx
+    pub keep_alive: Option<String>, // Ollama: how long the model stays loaded, e.g. "10m", "1h", "-1" (forever)
+    pub is_reasoning: Option<bool>, // Reasoning models take a reasoning effort and no temperature
+    pub reasoning_effort: Option<String>, // Overrides `Config::reasoning_effort` for this model
+    pub system_role: Option<String>, // "system" or "developer"
+    pub verbosity: Option<String>,  // gpt-5: "low", "medium" or "high"
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Config {
-    pub api_provider: String, // "openai", "azure_openai", "azure_translator", or "ollama"
+    pub api_provider: String, // "openai", "openai_compatible", "anthropic", "gemini", "azure_openai", "azure_translator", "deepl", "libretranslate", or "ollama"
     pub openai_api_key: String,
+    pub openai_compatible_base_url: String, // e.g. "http://localhost:1234/v1" for LM Studio
+    pub openai_compatible_api_key: String,  // Optional; many local servers need no key
+    pub anthropic_api_key: String,
+    pub gemini_api_key: String,
     pub azure_endpoint: String,
     pub azure_api_key: String,
     pub azure_api_version: String,
@@ -23,13 +37,20 @@ pub struct Config {
     pub azure_translator_endpoint: String,
     pub azure_translator_api_key: String,
     pub azure_translator_region: String,
+    pub deepl_api_key: String,
+    pub deepl_formality: String, // "default", "prefer_more", "prefer_less", "more", or "less"
+    pub deepl_glossary_id: Option<String>, // Only used when a source language override is set
+    pub libretranslate_url: String,
+    pub libretranslate_api_key: String, // Optional; self-hosted instances usually need no key
     pub ollama_url: Option<String>,
-    pub model: String, // Current selected model
+    pub ollama_warmup: bool, // Load the Ollama model at startup and after config changes
+    pub model: String,       // Current selected model
     pub available_models: HashMap<String, Vec<ModelConfig>>, // Provider -> Models
-    pub target_language: String, // User-specified target language (e.g., "Spanish", "French", "German")
+    pub target_language: String, // BCP-47 tag of the target language (e.g., "es", "fr", "zh-Hant")
     pub alternative_target_language: String, // Used when detected language is same as target language
-    pub favorite_languages: Vec<String>,     // User's favorite language codes for quick access
+    pub favorite_languages: Vec<String>,     // User's favorite language tags for quick access
     pub user_source_language: Option<String>, // Manual source language override (None = auto-detect)
+    pub transliteration_languages: Vec<String>, // Target languages whose translation also gets a romanization
     pub auto_start: bool,
     pub hotkey: String,
     pub theme: String,
@@ -37,10 +58,14 @@ pub struct Config {
     pub custom_prompt: String,
     pub reasoning_effort: Option<String>, // "minimal", "low", "medium", "high"
     pub alternatives_fallback_provider: Option<String>,
+    pub failover_providers: Vec<String>, // "provider:model" entries tried in order when the active provider fails
     pub auto_translate_enabled: bool,
     pub auto_translate_debounce_ms: u32,
     pub auto_translate_on_paste: bool,
     pub auto_translate_while_typing: bool,
+    pub stream_translations: bool, // Show partial output while the provider is still generating
+    pub retry_max_attempts: u32,   // Attempts per provider request, including the first one
+    pub retry_deadline_secs: u64,  // Stop retrying once a request has taken this long
 }
 
 impl Default for Config {
@@ -49,6 +74,9 @@ impl Default for Config {
 
         // Start with empty model arrays - users should configure their own models
         available_models.insert("openai".to_string(), vec![]);
+        available_models.insert("openai_compatible".to_string(), vec![]);
+        available_models.insert("anthropic".to_string(), vec![]);
+        available_models.insert("gemini".to_string(), vec![]);
         available_models.insert("azure_openai".to_string(), vec![]);
         available_models.insert("azure_translator".to_string(), vec![]);
         available_models.insert("ollama".to_string(), vec![]);
@@ -56,6 +84,10 @@ impl Default for Config {
         Self {
             api_provider: "".to_string(),
             openai_api_key: "".to_string(),
+            openai_compatible_base_url: "".to_string(),
+            openai_compatible_api_key: "".to_string(),
+            anthropic_api_key: "".to_string(),
+            gemini_api_key: "".to_string(),
             azure_endpoint: "".to_string(),
             azure_api_key: "".to_string(),
             azure_api_version: "".to_string(),
@@ -63,13 +95,20 @@ impl Default for Config {
             azure_translator_endpoint: "https://api.cognitive.microsofttranslator.com".to_string(),
             azure_translator_api_key: "".to_string(),
             azure_translator_region: "".to_string(),
+            deepl_api_key: "".to_string(),
+            deepl_formality: "default".to_string(),
+            deepl_glossary_id: None,
+            libretranslate_url: "".to_string(),
+            libretranslate_api_key: "".to_string(),
             ollama_url: Some("".to_string()),
+            ollama_warmup: false,
             model: "".to_string(),
             available_models,
-            target_language: "English".to_string(),
-            alternative_target_language: "Spanish".to_string(),
+            target_language: "en".to_string(),
+            alternative_target_language: "es".to_string(),
             favorite_languages: vec!["en".to_string(), "es".to_string(), "fr".to_string(), "de".to_string()],
             user_source_language: None,
+            transliteration_languages: vec![],
             auto_start: false,
             hotkey: "Ctrl+Q".to_string(),
             theme: "auto".to_string(),
@@ -77,17 +116,47 @@ impl Default for Config {
             custom_prompt: "Translate the given text from {detected_language} to {target_language} accurately while preserving the meaning, tone, and nuance of the original content.\n\n# Additional Details\n- Ensure the translation retains the context, cultural meaning, tone, formal/informal style, and any idiomatic expressions.\n- Do **not** alter names, technical terms, or specific formatting unless required for grammatical correctness in the target language.\n- If the detected language is the same as the target language, choose the most appropriate alternative language for translation.\n\n# Output Format\nThe translation output should be provided as valid JSON containing 'detected_language' and 'translated_text' fields.\n\n# Notes\n- Ensure punctuation and capitalization match the norms of the target language.\n- When encountering idiomatic expressions, adapt them to equivalent phrases in the target language rather than direct word-for-word translation.\n- For ambiguous content, aim for the most contextually appropriate meaning.\n- Take into consideration the whole text and what it is about.".to_string(),
             reasoning_effort: Some("medium".to_string()),
             alternatives_fallback_provider: None,
+            failover_providers: vec![],
             auto_translate_enabled: true,
             auto_translate_debounce_ms: 500,
             auto_translate_on_paste: true,
             auto_translate_while_typing: true,
+            stream_translations: true,
+            retry_max_attempts: 3,
+            retry_deadline_secs: 30,
         }
     }
 }
 
 impl Config {
     pub fn parse_alternatives_fallback(&self) -> Option<(String, String)> {
-        let raw = self.alternatives_fallback_provider.as_deref()?.trim();
+        let (provider, model) =
+            self.parse_provider_model(self.alternatives_fallback_provider.as_deref()?)?;
+
+        // Model-less providers are machine translation engines, which cannot generate alternatives
+        if is_modelless_provider(&provider) {
+            log::warn!(
+                "Alternatives fallback provider '{}' cannot generate alternatives",
+                provider
+            );
+            return None;
+        }
+
+        Some((provider, model))
+    }
+
+    /// Failover chain entries that resolve to a provider and model, in configured order.
+    pub fn parse_failover_providers(&self) -> Vec<(String, String)> {
+        self.failover_providers
+            .iter()
+            .filter_map(|entry| self.parse_provider_model(entry))
+            .collect()
+    }
+
+    /// Parses a "provider:model" entry. When only the provider is given, the model is
+    /// resolved from the configured models for that provider.
+    pub fn parse_provider_model(&self, raw: &str) -> Option<(String, String)> {
+        let raw = raw.trim();
         if raw.is_empty() {
             return None;
         }
@@ -99,9 +168,13 @@ impl Config {
 
         // Validate provider
         match provider.as_str() {
-            "openai" | "azure_openai" | "ollama" => {}
+            "openai" | "openai_compatible" | "anthropic" | "gemini" | "azure_openai" | "ollama" => {
+            }
+            other if is_modelless_provider(other) => {
+                return Some((provider, String::new()));
+            }
             other => {
-                log::warn!("Unknown alternatives fallback provider '{}'", other);
+                log::warn!("Unknown provider '{}' in '{}'", other, raw);
                 return None;
             }
         }
@@ -142,7 +215,7 @@ impl Config {
 
         if model.is_empty() {
             log::warn!(
-                "Could not resolve a model for alternatives fallback provider '{}'. Check Settings → Model Management.",
+                "Could not resolve a model for provider '{}'. Check Settings → Model Management.",
                 provider
             );
             return None;
@@ -151,6 +224,14 @@ impl Config {
         Some((provider, model))
     }
 
+    /// The model list entry for `model` of `provider`, which holds its generation settings.
+    pub fn model_config(&self, provider: &str, model: &str) -> Option<&ModelConfig> {
+        self.available_models
+            .get(provider)?
+            .iter()
+            .find(|m| m.name == model)
+    }
+
     /// Ensures that for Azure OpenAI, the deployment name matches the model name
     pub fn ensure_azure_deployment_consistency(&mut self) {
         if self.api_provider == "azure_openai"
@@ -189,7 +270,12 @@ impl Config {
 
             // Try to parse the config, and if it fails due to missing fields, migrate it
             match serde_json::from_str::<Config>(&content) {
-                Ok(config) => Ok(config),
+                Ok(mut config) => {
+                    if config.migrate_language_tags() {
+                        config.save()?;
+                    }
+                    Ok(config)
+                }
                 Err(_) => {
                     // Try to parse as a generic Value first to preserve existing settings
                     if let Ok(mut value) = serde_json::from_str::<serde_json::Value>(&content) {
@@ -201,7 +287,7 @@ impl Config {
                         } // Add alternative_target_language if missing
                         if value.get("alternative_target_language").is_none() {
                             value["alternative_target_language"] =
-                                serde_json::Value::String("Norwegian".to_string());
+                                serde_json::Value::String("nb".to_string());
                         }
 
                         // Add favorite_languages if missing
@@ -219,10 +305,17 @@ impl Config {
                         if value.get("ollama_url").is_none() {
                             value["ollama_url"] =
                                 serde_json::Value::String("http://localhost:11434".to_string());
+                        }
+                        if value.get("ollama_warmup").is_none() {
+                            value["ollama_warmup"] = serde_json::Value::Bool(false);
                         } // Add available_models if missing with empty arrays
                         if value.get("available_models").is_none() {
                             let mut available_models = serde_json::Map::new();
                             available_models.insert("openai".to_string(), serde_json::json!([]));
+                            available_models
+                                .insert("openai_compatible".to_string(), serde_json::json!([]));
+                            available_models.insert("anthropic".to_string(), serde_json::json!([]));
+                            available_models.insert("gemini".to_string(), serde_json::json!([]));
                             available_models
                                 .insert("azure_openai".to_string(), serde_json::json!([]));
                             available_models
@@ -231,6 +324,47 @@ impl Config {
                             value["available_models"] = serde_json::Value::Object(available_models);
                         }
 
+                        // Add OpenAI-compatible provider fields if missing
+                        if value.get("openai_compatible_base_url").is_none() {
+                            value["openai_compatible_base_url"] =
+                                serde_json::Value::String("".to_string());
+                        }
+                        if value.get("openai_compatible_api_key").is_none() {
+                            value["openai_compatible_api_key"] =
+                                serde_json::Value::String("".to_string());
+                        }
+                        if let Some(models) = value
+                            .get_mut("available_models")
+                            .and_then(|m| m.as_object_mut())
+                            && !models.contains_key("openai_compatible")
+                        {
+                            models.insert("openai_compatible".to_string(), serde_json::json!([]));
+                        }
+
+                        // Add Anthropic provider fields if missing
+                        if value.get("anthropic_api_key").is_none() {
+                            value["anthropic_api_key"] = serde_json::Value::String("".to_string());
+                        }
+                        if let Some(models) = value
+                            .get_mut("available_models")
+                            .and_then(|m| m.as_object_mut())
+                            && !models.contains_key("anthropic")
+                        {
+                            models.insert("anthropic".to_string(), serde_json::json!([]));
+                        }
+
+                        // Add Gemini provider fields if missing
+                        if value.get("gemini_api_key").is_none() {
+                            value["gemini_api_key"] = serde_json::Value::String("".to_string());
+                        }
+                        if let Some(models) = value
+                            .get_mut("available_models")
+                            .and_then(|m| m.as_object_mut())
+                            && !models.contains_key("gemini")
+                        {
+                            models.insert("gemini".to_string(), serde_json::json!([]));
+                        }
+
                         // Add azure_translator fields if missing
                         if value.get("azure_translator_endpoint").is_none() {
                             value["azure_translator_endpoint"] = serde_json::Value::String(
@@ -244,6 +378,27 @@ impl Config {
                         if value.get("azure_translator_region").is_none() {
                             value["azure_translator_region"] =
                                 serde_json::Value::String("".to_string());
+                        }
+
+                        // Add DeepL fields if missing
+                        if value.get("deepl_api_key").is_none() {
+                            value["deepl_api_key"] = serde_json::Value::String("".to_string());
+                        }
+                        if value.get("deepl_formality").is_none() {
+                            value["deepl_formality"] =
+                                serde_json::Value::String("default".to_string());
+                        }
+                        if value.get("deepl_glossary_id").is_none() {
+                            value["deepl_glossary_id"] = serde_json::Value::Null;
+                        }
+
+                        // Add LibreTranslate fields if missing
+                        if value.get("libretranslate_url").is_none() {
+                            value["libretranslate_url"] = serde_json::Value::String("".to_string());
+                        }
+                        if value.get("libretranslate_api_key").is_none() {
+                            value["libretranslate_api_key"] =
+                                serde_json::Value::String("".to_string());
                         } // Set empty model if it was using old defaults
                         if let Some(model) = value.get("model")
                             && model.as_str() == Some("gpt-4o-mini")
@@ -267,6 +422,16 @@ impl Config {
                             value["alternatives_fallback_provider"] = serde_json::Value::Null;
                         }
 
+                        // Add transliteration_languages if missing
+                        if value.get("transliteration_languages").is_none() {
+                            value["transliteration_languages"] = serde_json::Value::Array(vec![]);
+                        }
+
+                        // Add failover_providers if missing
+                        if value.get("failover_providers").is_none() {
+                            value["failover_providers"] = serde_json::Value::Array(vec![]);
+                        }
+
                         // Add automatic translation fields if missing
                         if value.get("auto_translate_enabled").is_none() {
                             value["auto_translate_enabled"] = serde_json::Value::Bool(true);
@@ -282,19 +447,34 @@ impl Config {
                             value["auto_translate_while_typing"] = serde_json::Value::Bool(true);
                         }
 
+                        // Add stream_translations if missing
+                        if value.get("stream_translations").is_none() {
+                            value["stream_translations"] = serde_json::Value::Bool(true);
+                        }
+
+                        // Add retry settings if missing
+                        if value.get("retry_max_attempts").is_none() {
+                            value["retry_max_attempts"] =
+                                serde_json::Value::Number(serde_json::Number::from(3));
+                        }
+                        if value.get("retry_deadline_secs").is_none() {
+                            value["retry_deadline_secs"] =
+                                serde_json::Value::Number(serde_json::Number::from(30));
+                        }
+
                         // Ensure target_language has a sensible default if it was "auto"
                         if let Some(target_lang) = value.get("target_language") {
                             if target_lang.as_str() == Some("auto") {
                                 value["target_language"] =
-                                    serde_json::Value::String("English".to_string());
+                                    serde_json::Value::String("en".to_string());
                             }
                         } else {
-                            value["target_language"] =
-                                serde_json::Value::String("English".to_string());
+                            value["target_language"] = serde_json::Value::String("en".to_string());
                         }
 
                         // Try to parse again with the migrated config
-                        let migrated_config: Config = serde_json::from_value(value)?;
+                        let mut migrated_config: Config = serde_json::from_value(value)?;
+                        migrated_config.migrate_language_tags();
                         migrated_config.save()?; // Save the migrated config
                         Ok(migrated_config)
                     } else {
@@ -312,6 +492,24 @@ impl Config {
         }
     }
 
+    /// Older configs stored languages by English name ("Norwegian") or by an older code
+    /// ("zh-cn"); rewrites them as tags. Returns whether anything changed.
+    fn migrate_language_tags(&mut self) -> bool {
+        let mut changed = languages::migrate_to_tag(&mut self.target_language);
+        changed |= languages::migrate_to_tag(&mut self.alternative_target_language);
+        if let Some(source) = self.user_source_language.as_mut() {
+            changed |= languages::migrate_to_tag(source);
+        }
+        for language in self
+            .favorite_languages
+            .iter_mut()
+            .chain(self.transliteration_languages.iter_mut())
+        {
+            changed |= languages::migrate_to_tag(language);
+        }
+        changed
+    }
+
     pub fn save(&self) -> Result<()> {
         let config_path = Self::get_config_path()?;
         let content = serde_json::to_string_pretty(self)?;
@@ -319,3 +517,8 @@ impl Config {
         Ok(())
     }
 }
+
+/// Dedicated machine translation engines are used without choosing a model.
+fn is_modelless_provider(provider: &str) -> bool {
+    matches!(provider, "azure_translator" | "deepl" | "libretranslate")
+}
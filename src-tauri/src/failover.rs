@@ -0,0 +1,155 @@
+use crate::config::Config;
+use crate::translation::{ProviderError, ProviderErrorKind};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Consecutive failures after which a provider is skipped for `OPEN_DURATION`.
+const FAILURE_THRESHOLD: u32 = 3;
+const OPEN_DURATION: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct Circuit {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+lazy_static! {
+    // Keyed by "provider:model"
+    static ref CIRCUITS: Mutex<HashMap<String, Circuit>> = Mutex::new(HashMap::new());
+}
+
+fn circuit_key(config: &Config) -> String {
+    format!("{}:{}", config.api_provider, config.model)
+}
+
+fn is_open(key: &str) -> bool {
+    // Once the open period is over, the next request is let through as a probe
+    CIRCUITS
+        .lock()
+        .unwrap()
+        .get(key)
+        .and_then(|circuit| circuit.open_until)
+        .is_some_and(|open_until| Instant::now() < open_until)
+}
+
+/// The configs to try in order: the active provider followed by the failover providers,
+/// skipping duplicates and providers whose circuit is open. If every circuit is open the
+/// whole chain is returned, since failing without trying anything helps nobody.
+pub fn provider_chain(config: &Config) -> Vec<Config> {
+    let mut chain = vec![config.clone()];
+    for (provider, model) in config.parse_failover_providers() {
+        let mut candidate = config.clone();
+        // For Azure OpenAI, the deployment is the one named by the model
+        if provider == "azure_openai" {
+            candidate.azure_deployment_name = model.clone();
+        }
+        candidate.api_provider = provider;
+        candidate.model = model;
+        if !chain
+            .iter()
+            .any(|c| circuit_key(c) == circuit_key(&candidate))
+        {
+            chain.push(candidate);
+        }
+    }
+
+    let available: Vec<Config> = chain
+        .iter()
+        .filter(|candidate| {
+            let open = is_open(&circuit_key(candidate));
+            if open {
+                log::info!(
+                    "Skipping '{}': circuit open after repeated failures",
+                    circuit_key(candidate)
+                );
+            }
+            !open
+        })
+        .cloned()
+        .collect();
+
+    if available.is_empty() {
+        log::warn!("All providers in the failover chain are failing, trying them anyway");
+        chain
+    } else {
+        available
+    }
+}
+
+pub fn record_success(config: &Config) {
+    if let Some(circuit) = CIRCUITS.lock().unwrap().get_mut(&circuit_key(config)) {
+        *circuit = Circuit::default();
+    }
+}
+
+pub fn record_failure(config: &Config) {
+    let key = circuit_key(config);
+    let mut circuits = CIRCUITS.lock().unwrap();
+    let circuit = circuits.entry(key.clone()).or_default();
+    circuit.consecutive_failures += 1;
+
+    if circuit.consecutive_failures >= FAILURE_THRESHOLD {
+        log::warn!(
+            "'{}' failed {} times in a row, skipping it for {} s",
+            key,
+            circuit.consecutive_failures,
+            OPEN_DURATION.as_secs()
+        );
+        circuit.open_until = Some(Instant::now() + OPEN_DURATION);
+    }
+}
+
+/// Errors that another provider may not run into: authentication and quota problems, content
+/// filters, outages, missing configuration and network failures. Anything else (e.g.
+/// unparseable replies) is reported as is.
+pub fn should_fail_over(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<ProviderError>() {
+            return match e.kind {
+                ProviderErrorKind::Status(status) => matches!(
+                    status.as_u16(),
+                    401 | 402 | 403 | 408 | 429 | 500 | 502 | 503 | 504 | 529
+                ),
+                ProviderErrorKind::NotConfigured | ProviderErrorKind::Blocked => true,
+            };
+        }
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect() || e.is_timeout() || e.is_request())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fail_over_on_error_kind_not_message() {
+        let status = |code: u16, message: &str| {
+            ProviderError::status(
+                reqwest::StatusCode::from_u16(code).unwrap(),
+                message.to_string(),
+            )
+        };
+        assert!(should_fail_over(&status(429, "Too many requests")));
+        assert!(should_fail_over(&status(503, "Service unavailable")));
+        assert!(should_fail_over(&ProviderError::not_configured(
+            "Model not configured"
+        )));
+        assert!(should_fail_over(&ProviderError::blocked(
+            "Content filtered".to_string()
+        )));
+        assert!(!should_fail_over(&status(
+            400,
+            "Request blocked: permission denied (401)"
+        )));
+        assert!(!should_fail_over(&anyhow::anyhow!(
+            "Authentication quota exceeded (429)"
+        )));
+        assert!(should_fail_over(
+            &status(401, "Unauthorized").context("Chunk 2 failed")
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::languages;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -9,19 +10,25 @@ pub struct TranslationEntry {
     pub id: String,
     pub original_text: String,
     pub translated_text: String,
-    pub detected_language: String,
-    pub target_language: String,
+    pub detected_language: String, // Language tag, or "unknown"
+    pub target_language: String,   // Language tag
     pub timestamp: DateTime<Utc>,
 }
 
+/// Bumped when stored entries need migrating. Files without a version predate language tags.
+const HISTORY_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, Default)]
 pub struct TranslationHistory {
+    #[serde(default)]
+    pub version: u32,
     pub entries: Vec<TranslationEntry>,
 }
 
 impl TranslationHistory {
     pub fn new() -> Self {
         Self {
+            version: HISTORY_VERSION,
             entries: Vec::new(),
         }
     }
@@ -65,12 +72,26 @@ pub fn load_history() -> Result<TranslationHistory> {
     }
 
     let contents = fs::read_to_string(history_path)?;
-    let history: TranslationHistory =
+    let mut history: TranslationHistory =
         serde_json::from_str(&contents).unwrap_or_else(|_| TranslationHistory::new());
 
+    if history.version < HISTORY_VERSION {
+        migrate_language_tags(&mut history);
+        history.version = HISTORY_VERSION;
+        save_history(&history)?;
+    }
+
     Ok(history)
 }
 
+/// Older entries stored languages by English name ("Norwegian"); rewrites them as tags.
+fn migrate_language_tags(history: &mut TranslationHistory) {
+    for entry in &mut history.entries {
+        languages::migrate_name_to_tag(&mut entry.detected_language);
+        languages::migrate_name_to_tag(&mut entry.target_language);
+    }
+}
+
 fn migrate_history_if_needed() -> Result<()> {
     let home_dir =
         dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
@@ -260,40 +281,3 @@ pub fn delete_history_entry(entry_id: String) -> Result<()> {
     save_history(&history)?;
     Ok(())
 }
-
-pub fn fix_target_language_in_history() -> Result<()> {
-    let mut history = load_history()?;
-
-    for entry in &mut history.entries {
-        // If the target language is "English" but the translated text appears to be Norwegian,
-        // update the target language to "Norwegian"
-        if entry.target_language == "English" && entry.detected_language == "English" {
-            // Simple heuristic: check if translated text contains Norwegian characters or common Norwegian words
-            let norwegian_indicators = [
-                "å", "æ", "ø", "Å", "Æ", "Ø", // Norwegian letters
-                " og ", " av ", " på ", " med ", " til ", " for ", " ikke ", " kan ", " det ",
-                " er ", // Common Norwegian words
-            ];
-
-            let has_norwegian_indicators = norwegian_indicators
-                .iter()
-                .any(|&indicator| entry.translated_text.contains(indicator));
-
-            // Also check for Norwegian sentence patterns (words ending in common Norwegian suffixes)
-            let norwegian_suffixes = ["ene", "ing", "het", "else"];
-            let has_norwegian_suffixes = norwegian_suffixes.iter().any(|&suffix| {
-                entry
-                    .translated_text
-                    .split_whitespace()
-                    .any(|word| word.to_lowercase().ends_with(suffix))
-            });
-
-            if has_norwegian_indicators || has_norwegian_suffixes {
-                entry.target_language = "Norwegian".to_string();
-            }
-        }
-    }
-
-    save_history(&history)?;
-    Ok(())
-}
@@ -0,0 +1,680 @@
+use crate::config::Config;
+use crate::trans_azure_translator::AzureTranslatorService;
+use crate::trans_deepl::DeepLTranslationService;
+use crate::trans_libretranslate::LibreTranslateService;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use whatlang::Lang;
+
+/// A language offered for translation, identified by its BCP-47 tag (`code`). Config, history
+/// and translation requests store the tag; the names are only used in prompts and the UI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Language {
+    pub code: String, // BCP-47 tag, e.g. "en", "zh-Hant", "nb"
+    pub english_name: String,
+    pub native_name: String,
+    pub script: Option<String>, // ISO 15924 script, unknown for languages only a provider lists
+    pub common_names: Vec<String>, // Other names the language is matched by
+    pub providers: BTreeMap<String, String>, // Provider id -> that provider's code
+}
+
+/// An entry of a provider's own language list.
+#[derive(Debug, Clone)]
+pub struct ProviderLanguage {
+    pub code: String,
+    pub name: String,
+    pub native_name: Option<String>,
+}
+
+// The registry used when no provider list is available:
+// (BCP-47 tag, English name, native name, ISO 15924 script, whatlang ISO 639-3 code)
+const BUILTIN_LANGUAGES: &[(&str, &str, &str, &str, Option<&str>)] = &[
+    ("en", "English", "English", "Latn", Some("eng")),
+    (
+        "en-US",
+        "English (United States)",
+        "English (US)",
+        "Latn",
+        None,
+    ),
+    (
+        "en-GB",
+        "English (United Kingdom)",
+        "English (UK)",
+        "Latn",
+        None,
+    ),
+    ("es", "Spanish", "Español", "Latn", Some("spa")),
+    ("fr", "French", "Français", "Latn", Some("fra")),
+    ("de", "German", "Deutsch", "Latn", Some("deu")),
+    ("it", "Italian", "Italiano", "Latn", Some("ita")),
+    ("pt", "Portuguese", "Português", "Latn", Some("por")),
+    (
+        "pt-BR",
+        "Portuguese (Brazil)",
+        "Português (Brasil)",
+        "Latn",
+        None,
+    ),
+    (
+        "pt-PT",
+        "Portuguese (Portugal)",
+        "Português (Portugal)",
+        "Latn",
+        None,
+    ),
+    ("ru", "Russian", "Русский", "Cyrl", Some("rus")),
+    ("ja", "Japanese", "日本語", "Jpan", Some("jpn")),
+    ("ko", "Korean", "한국어", "Kore", Some("kor")),
+    (
+        "zh-Hans",
+        "Chinese (Simplified)",
+        "简体中文",
+        "Hans",
+        Some("cmn"),
+    ),
+    ("zh-Hant", "Chinese (Traditional)", "繁體中文", "Hant", None),
+    ("ar", "Arabic", "العربية", "Arab", Some("ara")),
+    ("hi", "Hindi", "हिन्दी", "Deva", Some("hin")),
+    ("th", "Thai", "ไทย", "Thai", Some("tha")),
+    ("vi", "Vietnamese", "Tiếng Việt", "Latn", Some("vie")),
+    ("nl", "Dutch", "Nederlands", "Latn", Some("nld")),
+    ("sv", "Swedish", "Svenska", "Latn", Some("swe")),
+    ("da", "Danish", "Dansk", "Latn", Some("dan")),
+    (
+        "nb",
+        "Norwegian Bokmål",
+        "Norsk bokmål",
+        "Latn",
+        Some("nob"),
+    ),
+    ("nn", "Norwegian Nynorsk", "Norsk nynorsk", "Latn", None),
+    ("fi", "Finnish", "Suomi", "Latn", Some("fin")),
+    ("pl", "Polish", "Polski", "Latn", Some("pol")),
+    ("cs", "Czech", "Čeština", "Latn", Some("ces")),
+    ("sk", "Slovak", "Slovenčina", "Latn", Some("slk")),
+    ("hu", "Hungarian", "Magyar", "Latn", Some("hun")),
+    ("ro", "Romanian", "Română", "Latn", Some("ron")),
+    ("bg", "Bulgarian", "Български", "Cyrl", Some("bul")),
+    ("hr", "Croatian", "Hrvatski", "Latn", Some("hrv")),
+    ("sr", "Serbian", "Српски", "Cyrl", Some("srp")),
+    (
+        "sr-Latn",
+        "Serbian (Latin)",
+        "Srpski (latinica)",
+        "Latn",
+        None,
+    ),
+    (
+        "sr-Cyrl",
+        "Serbian (Cyrillic)",
+        "Српски (ћирилица)",
+        "Cyrl",
+        None,
+    ),
+    ("sl", "Slovenian", "Slovenščina", "Latn", Some("slv")),
+    ("et", "Estonian", "Eesti", "Latn", Some("est")),
+    ("lv", "Latvian", "Latviešu", "Latn", Some("lav")),
+    ("lt", "Lithuanian", "Lietuvių", "Latn", Some("lit")),
+    ("uk", "Ukrainian", "Українська", "Cyrl", Some("ukr")),
+    ("be", "Belarusian", "Беларуская", "Cyrl", Some("bel")),
+    ("mk", "Macedonian", "Македонски", "Cyrl", Some("mkd")),
+    ("sq", "Albanian", "Shqip", "Latn", None),
+    ("mt", "Maltese", "Malti", "Latn", None),
+    ("is", "Icelandic", "Íslenska", "Latn", None),
+    ("ga", "Irish", "Gaeilge", "Latn", None),
+    ("cy", "Welsh", "Cymraeg", "Latn", None),
+    ("eu", "Basque", "Euskera", "Latn", None),
+    ("ca", "Catalan", "Català", "Latn", Some("cat")),
+    ("gl", "Galician", "Galego", "Latn", None),
+    ("el", "Greek", "Ελληνικά", "Grek", Some("ell")),
+    ("tr", "Turkish", "Türkçe", "Latn", Some("tur")),
+    ("he", "Hebrew", "עברית", "Hebr", Some("heb")),
+    ("fa", "Persian", "فارسی", "Arab", Some("pes")),
+    ("ur", "Urdu", "اردو", "Arab", Some("urd")),
+    ("bn", "Bengali", "বাংলা", "Beng", Some("ben")),
+    ("gu", "Gujarati", "ગુજરાતી", "Gujr", Some("guj")),
+    ("pa", "Punjabi", "ਪੰਜਾਬੀ", "Guru", Some("pan")),
+    ("ta", "Tamil", "தமிழ்", "Taml", Some("tam")),
+    ("te", "Telugu", "తెలుగు", "Telu", Some("tel")),
+    ("kn", "Kannada", "ಕನ್ನಡ", "Knda", Some("kan")),
+    ("ml", "Malayalam", "മലയാളം", "Mlym", Some("mal")),
+    ("mr", "Marathi", "मराठी", "Deva", Some("mar")),
+    ("ne", "Nepali", "नेपाली", "Deva", Some("nep")),
+    ("si", "Sinhala", "සිංහල", "Sinh", Some("sin")),
+    ("my", "Burmese", "မြန်မာ", "Mymr", Some("mya")),
+    ("km", "Khmer", "ខ្មែរ", "Khmr", Some("khm")),
+    ("lo", "Lao", "ລາວ", "Laoo", None),
+    ("ka", "Georgian", "ქართული", "Geor", Some("kat")),
+    ("am", "Amharic", "አማርኛ", "Ethi", Some("amh")),
+    ("sw", "Swahili", "Kiswahili", "Latn", None),
+    ("zu", "Zulu", "isiZulu", "Latn", Some("zul")),
+    ("af", "Afrikaans", "Afrikaans", "Latn", Some("afr")),
+    ("yo", "Yoruba", "Yorùbá", "Latn", None),
+    ("ig", "Igbo", "Asụsụ Igbo", "Latn", None),
+    ("ha", "Hausa", "Harshen Hausa", "Latn", None),
+    ("ms", "Malay", "Bahasa Melayu", "Latn", None),
+    ("id", "Indonesian", "Bahasa Indonesia", "Latn", Some("ind")),
+    ("fil", "Filipino", "Filipino", "Latn", Some("tgl")),
+    ("haw", "Hawaiian", "ʻŌlelo Hawaiʻi", "Latn", None),
+    ("mi", "Maori", "Te Reo Māori", "Latn", None),
+    ("sm", "Samoan", "Gagana Samoa", "Latn", None),
+    ("to", "Tongan", "Lea Fakatonga", "Latn", None),
+    ("fj", "Fijian", "Na Vosa Vakaviti", "Latn", None),
+    ("az", "Azerbaijani", "Azərbaycan dili", "Latn", Some("aze")),
+    ("kk", "Kazakh", "Қазақ тілі", "Cyrl", None),
+    ("ky", "Kyrgyz", "Кыргыз тили", "Cyrl", None),
+    ("uz", "Uzbek", "Oʻzbek tili", "Latn", Some("uzb")),
+    ("tg", "Tajik", "Тоҷикӣ", "Cyrl", None),
+    ("tk", "Turkmen", "Türkmen dili", "Latn", Some("tuk")),
+    ("mn", "Mongolian", "Монгол хэл", "Cyrl", None),
+    ("bo", "Tibetan", "བོད་སྐད།", "Tibt", None),
+    ("dv", "Maldivian", "ދިވެހިބަސް", "Thaa", None),
+    ("ps", "Pashto", "پښتو", "Arab", None),
+    ("sd", "Sindhi", "سنڌي", "Arab", None),
+    ("ku", "Kurdish", "Kurdî", "Latn", None),
+    ("yi", "Yiddish", "ייִדיש", "Hebr", Some("yid")),
+    ("la", "Latin", "Latina", "Latn", Some("lat")),
+    ("eo", "Esperanto", "Esperanto", "Latn", Some("epo")),
+    ("jv", "Javanese", "Basa Jawa", "Latn", Some("jav")),
+    ("su", "Sundanese", "Basa Sunda", "Latn", None),
+    ("ceb", "Cebuano", "Sinugbuanong Binisaya", "Latn", None),
+    ("ny", "Chichewa", "ChiCheŵa", "Latn", None),
+    ("co", "Corsican", "Corsu", "Latn", None),
+    ("fy", "Frisian", "Frysk", "Latn", None),
+    ("gd", "Scottish Gaelic", "Gàidhlig", "Latn", None),
+    ("hmn", "Hmong", "Hmoob", "Latn", None),
+    ("lb", "Luxembourgish", "Lëtzebuergesch", "Latn", None),
+    ("mg", "Malagasy", "Malagasy", "Latn", None),
+    ("st", "Sesotho", "Sesotho", "Latn", None),
+    ("sn", "Shona", "ChiShona", "Latn", Some("sna")),
+    ("so", "Somali", "Soomaaliga", "Latn", None),
+    ("xh", "Xhosa", "isiXhosa", "Latn", None),
+    ("hy", "Armenian", "Հայերեն", "Armn", Some("hye")),
+    ("or", "Odia", "ଓଡ଼ିଆ", "Orya", Some("ori")),
+    ("ak", "Akan", "Akan", "Latn", Some("aka")),
+];
+
+// Other names a language goes by
+const COMMON_NAMES: &[(&str, &[&str])] = &[
+    ("es", &["Castilian"]),
+    ("zh-Hans", &["Chinese", "Mandarin"]),
+    ("en-US", &["American English"]),
+    ("en-GB", &["British English"]),
+    ("pt-BR", &["Brazilian Portuguese"]),
+    ("pt-PT", &["European Portuguese"]),
+    ("nb", &["Norwegian", "Bokmal"]),
+    ("nn", &["Nynorsk"]),
+    ("fa", &["Farsi"]),
+    ("fil", &["Tagalog"]),
+    ("sl", &["Slovene"]),
+    ("si", &["Sinhalese"]),
+    ("my", &["Myanmar"]),
+    ("or", &["Oriya"]),
+];
+
+// Other codes that refer to a language: the catalogue codes used before tags, and
+// provider-specific codes
+const CODE_ALIASES: &[(&str, &[&str])] = &[
+    ("zh-Hans", &["zh-cn", "zh", "zh-SG"]),
+    ("zh-Hant", &["zh-tw", "zt", "zh-HK"]),
+    ("nb", &["no"]),
+    ("fil", &["tl"]),
+    ("jv", &["jw"]),
+    ("hmn", &["hm", "mww"]),
+    ("he", &["iw"]),
+    ("mn", &["mn-Cyrl"]),
+];
+
+// Azure Translator codes that differ from the tag. Azure has a single English, and its "pt"
+// is Brazilian Portuguese.
+const AZURE_CODES: &[(&str, &str)] = &[
+    ("en-US", "en"),
+    ("en-GB", "en"),
+    ("pt-BR", "pt"),
+    ("hmn", "mww"),
+    ("mn", "mn-Cyrl"),
+    ("sr", "sr-Cyrl"),
+];
+
+// Spelling and locale conventions of variants, spelled out for LLM providers, which otherwise
+// tend to fall back to the most common variant
+const CONVENTIONS: &[(&str, &str)] = &[
+    (
+        "en-US",
+        "American spelling and vocabulary (color, organize, apartment), MM/DD/YYYY dates",
+    ),
+    (
+        "en-GB",
+        "British spelling and vocabulary (colour, organise, flat), DD/MM/YYYY dates",
+    ),
+    (
+        "pt-BR",
+        "Brazilian Portuguese spelling and vocabulary (ônibus, celular, você)",
+    ),
+    (
+        "pt-PT",
+        "European Portuguese spelling and vocabulary (autocarro, telemóvel, tu)",
+    ),
+    (
+        "zh-Hans",
+        "Simplified Chinese characters, as used in mainland China",
+    ),
+    (
+        "zh-Hant",
+        "Traditional Chinese characters, as used in Taiwan and Hong Kong",
+    ),
+    ("nb", "the Bokmål written standard"),
+    ("nn", "the Nynorsk written standard"),
+    ("sr-Latn", "Latin script (latinica)"),
+    ("sr-Cyrl", "Cyrillic script (ћирилица)"),
+];
+
+// Languages that are variants of one macrolanguage, which smart switching treats as a match
+const MACROLANGUAGES: &[(&str, &str)] = &[("nb", "no"), ("nn", "no")];
+
+lazy_static! {
+    static ref CATALOGUE: Mutex<Vec<Language>> = Mutex::new(builtin_catalogue());
+}
+
+fn builtin_catalogue() -> Vec<Language> {
+    BUILTIN_LANGUAGES
+        .iter()
+        .map(|&(code, english_name, native_name, script, _)| Language {
+            code: code.to_string(),
+            english_name: english_name.to_string(),
+            native_name: native_name.to_string(),
+            script: Some(script.to_string()),
+            common_names: COMMON_NAMES
+                .iter()
+                .filter(|(c, _)| *c == code)
+                .flat_map(|(_, names)| names.iter().map(|name| name.to_string()))
+                .collect(),
+            providers: AZURE_CODES
+                .iter()
+                .filter(|(c, _)| *c == code)
+                .map(|(_, azure)| ("azure_translator".to_string(), azure.to_string()))
+                .collect(),
+        })
+        .collect()
+}
+
+fn code_aliases(code: &str) -> &'static [&'static str] {
+    CODE_ALIASES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, aliases)| *aliases)
+        .unwrap_or(&[])
+}
+
+fn has_code(language: &Language, code: &str) -> bool {
+    language.code.eq_ignore_ascii_case(code)
+        || code_aliases(&language.code)
+            .iter()
+            .any(|alias| alias.eq_ignore_ascii_case(code))
+        || language
+            .providers
+            .values()
+            .any(|provider_code| provider_code.eq_ignore_ascii_case(code))
+}
+
+fn has_name(language: &Language, name: &str) -> bool {
+    language.english_name.eq_ignore_ascii_case(name)
+        || language.native_name.to_lowercase() == name.to_lowercase()
+        || language
+            .common_names
+            .iter()
+            .any(|common| common.eq_ignore_ascii_case(name))
+}
+
+/// Index of the language a tag or code refers to. Tags win over aliases and provider codes, so
+/// "sr-Cyrl" is Serbian (Cyrillic) even though Azure uses that code for plain Serbian too.
+fn position_by_code(catalogue: &[Language], code: &str) -> Option<usize> {
+    catalogue
+        .iter()
+        .position(|language| language.code.eq_ignore_ascii_case(code))
+        .or_else(|| {
+            catalogue
+                .iter()
+                .position(|language| has_code(language, code))
+        })
+}
+
+/// Index of the language a tag, code or name refers to. Regional codes such as "EN-US" fall
+/// back to their base language when the catalogue has no entry for the region.
+fn position(catalogue: &[Language], query: &str) -> Option<usize> {
+    let query = query.trim();
+    if query.is_empty() {
+        return None;
+    }
+    position_by_code(catalogue, query)
+        .or_else(|| {
+            catalogue
+                .iter()
+                .position(|language| has_name(language, query))
+        })
+        .or_else(|| {
+            let (base, _) = query.split_once(['-', '_'])?;
+            catalogue
+                .iter()
+                .position(|language| has_code(language, base))
+        })
+}
+
+/// Canonical casing for a tag a provider reports: "pt-br" and "PT-BR" become "pt-BR",
+/// "zh-hant" becomes "zh-Hant".
+fn canonical_tag(code: &str) -> String {
+    code.split(['-', '_'])
+        .enumerate()
+        .map(|(index, subtag)| match subtag.len() {
+            _ if index == 0 => subtag.to_lowercase(),
+            4 => {
+                let mut chars = subtag.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect()
+                })
+            }
+            _ => subtag.to_uppercase(),
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// The catalogue entry for a tag, a provider code or a name.
+pub fn find(query: &str) -> Option<Language> {
+    let catalogue = CATALOGUE.lock().unwrap();
+    position(&catalogue, query).map(|index| catalogue[index].clone())
+}
+
+/// The BCP-47 tag of a language tag, code or name. Unknown languages, such as custom ones the
+/// user typed in, are kept as they are.
+pub fn tag(query: &str) -> String {
+    find(query)
+        .map(|language| language.code)
+        .unwrap_or_else(|| query.trim().to_string())
+}
+
+/// The English name of a language, for prompts and logs. Unknown languages keep the query.
+pub fn english_name(query: &str) -> String {
+    find(query)
+        .map(|language| language.english_name)
+        .unwrap_or_else(|| query.to_string())
+}
+
+/// The ISO 15924 script a language is written in, when known.
+pub fn script(query: &str) -> Option<String> {
+    find(query).and_then(|language| language.script)
+}
+
+/// The tag of a language whatlang detected.
+pub fn from_whatlang(lang: Lang) -> Option<String> {
+    BUILTIN_LANGUAGES
+        .iter()
+        .find(|(.., whatlang)| *whatlang == Some(lang.code()))
+        .map(|(code, ..)| code.to_string())
+}
+
+/// The code `provider` uses for a language. Without a language list from that provider
+/// the tag is used.
+pub fn provider_code(provider: &str, query: &str) -> Option<String> {
+    let language = find(query)?;
+    Some(
+        language
+            .providers
+            .get(provider)
+            .cloned()
+            .unwrap_or(language.code),
+    )
+}
+
+/// Whether two tags, codes or names refer to the same language.
+pub fn same_language(a: &str, b: &str) -> bool {
+    if a.eq_ignore_ascii_case(b) {
+        return true;
+    }
+    let catalogue = CATALOGUE.lock().unwrap();
+    match (position(&catalogue, a), position(&catalogue, b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// The language a tag is a variant of: "pt" for "pt-BR" and "pt-PT", "no" for "nb" and "nn".
+fn base_language(tag: &str) -> String {
+    let base = tag.split(['-', '_']).next().unwrap_or(tag).to_lowercase();
+    MACROLANGUAGES
+        .iter()
+        .find(|(language, _)| *language == base)
+        .map(|(_, macrolanguage)| macrolanguage.to_string())
+        .unwrap_or(base)
+}
+
+/// Whether two languages are the same or variants of one language, such as "en-GB" and
+/// "en-US" or "nb" and "nn". Smart switching uses this: text in any variant of the primary
+/// target goes to the alternative target.
+pub fn same_base_language(a: &str, b: &str) -> bool {
+    if same_language(a, b) {
+        return true;
+    }
+    match (find(a), find(b)) {
+        (Some(a), Some(b)) => base_language(&a.code) == base_language(&b.code),
+        _ => false,
+    }
+}
+
+/// The spelling and locale conventions of a regional or script variant, for LLM prompts.
+pub fn conventions(query: &str) -> Option<&'static str> {
+    let tag = find(query)?.code;
+    CONVENTIONS
+        .iter()
+        .find(|(code, _)| *code == tag)
+        .map(|(_, conventions)| *conventions)
+}
+
+/// Rewrites a stored language (a name or an older code) as its tag. Returns whether it changed.
+pub fn migrate_to_tag(value: &mut String) -> bool {
+    let tag = tag(value);
+    if tag == *value {
+        return false;
+    }
+    log::info!("Migrating language '{}' to '{}'", value, tag);
+    *value = tag;
+    true
+}
+
+/// Rewrites a language stored by name ("Norwegian") as its tag. Tags and codes are left alone,
+/// so a region the catalogue lacks ("en-AU") is not collapsed to its base language.
+/// Returns whether it changed.
+pub fn migrate_name_to_tag(value: &mut String) -> bool {
+    let tag = CATALOGUE
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|language| has_name(language, value.trim()))
+        .map(|language| language.code.clone());
+    match tag {
+        Some(tag) if tag != *value => {
+            log::info!("Migrating language '{}' to '{}'", value, tag);
+            *value = tag;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// The current catalogue, served to the frontend.
+pub fn catalogue() -> Vec<Language> {
+    CATALOGUE.lock().unwrap().clone()
+}
+
+fn merge(catalogue: &mut Vec<Language>, provider: &str, languages: &[ProviderLanguage]) {
+    for reported in languages {
+        let index = position_by_code(catalogue, &reported.code).or_else(|| {
+            catalogue
+                .iter()
+                .position(|language| has_name(language, &reported.name))
+        });
+        match index {
+            Some(index) => {
+                // Keep the first code when a provider lists several variants of one language
+                catalogue[index]
+                    .providers
+                    .entry(provider.to_string())
+                    .or_insert_with(|| reported.code.clone());
+            }
+            None => catalogue.push(Language {
+                code: canonical_tag(&reported.code),
+                english_name: reported.name.clone(),
+                native_name: reported
+                    .native_name
+                    .clone()
+                    .unwrap_or_else(|| reported.name.clone()),
+                script: None,
+                common_names: Vec::new(),
+                providers: BTreeMap::from([(provider.to_string(), reported.code.clone())]),
+            }),
+        }
+    }
+}
+
+/// Rebuilds the catalogue from the built-in list and the language lists of the configured
+/// translation engines. Engines that cannot be reached are left out until the next refresh.
+pub async fn refresh(config: &Config) {
+    let mut catalogue = builtin_catalogue();
+
+    if !config.azure_translator_endpoint.trim().is_empty() {
+        match AzureTranslatorService::new(config.clone())
+            .supported_languages()
+            .await
+        {
+            Ok(languages) => merge(&mut catalogue, "azure_translator", &languages),
+            Err(e) => log::warn!("Failed to load Azure Translator languages: {}", e),
+        }
+    }
+
+    if !config.deepl_api_key.trim().is_empty() {
+        match DeepLTranslationService::new(config.clone())
+            .supported_languages()
+            .await
+        {
+            Ok(languages) => merge(&mut catalogue, "deepl", &languages),
+            Err(e) => log::warn!("Failed to load DeepL languages: {}", e),
+        }
+    }
+
+    if !config.libretranslate_url.trim().is_empty() {
+        let languages = LibreTranslateService::new(config.clone())
+            .supported_languages()
+            .await;
+        merge(&mut catalogue, "libretranslate", &languages);
+    }
+
+    log::info!("Language catalogue has {} languages", catalogue.len());
+    *CATALOGUE.lock().unwrap() = catalogue;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_codes_resolve_to_catalogue_languages() {
+        let catalogue = builtin_catalogue();
+        let name = |query: &str| {
+            position(&catalogue, query).map(|index| catalogue[index].english_name.as_str())
+        };
+        assert_eq!(name("zh-Hans"), Some("Chinese (Simplified)"));
+        assert_eq!(name("Chinese"), Some("Chinese (Simplified)"));
+        assert_eq!(name("ZH-HANT"), Some("Chinese (Traditional)"));
+        assert_eq!(name("EN-US"), Some("English (United States)"));
+        assert_eq!(name("en-AU"), Some("English"));
+        assert_eq!(name("nb"), Some("Norwegian Bokmål"));
+        assert_eq!(name("sr-Cyrl"), Some("Serbian (Cyrillic)"));
+        assert_eq!(name("PT-BR"), Some("Portuguese (Brazil)"));
+        assert_eq!(name("Klingon"), None);
+    }
+
+    #[test]
+    fn test_names_and_old_codes_resolve_to_tags() {
+        let catalogue = builtin_catalogue();
+        let tag =
+            |query: &str| position(&catalogue, query).map(|index| catalogue[index].code.as_str());
+        assert_eq!(tag("Norwegian"), Some("nb"));
+        assert_eq!(tag("no"), Some("nb"));
+        assert_eq!(tag("zh-cn"), Some("zh-Hans"));
+        assert_eq!(tag("Chinese (Traditional)"), Some("zh-Hant"));
+        assert_eq!(tag("tl"), Some("fil"));
+        assert_eq!(tag("mww"), Some("hmn"));
+        assert_eq!(canonical_tag("PT-BR"), "pt-BR");
+        assert_eq!(canonical_tag("sr-latn"), "sr-Latn");
+    }
+
+    #[test]
+    fn test_variants_map_to_provider_codes() {
+        assert_eq!(
+            provider_code("azure_translator", "en-GB").as_deref(),
+            Some("en")
+        );
+        assert_eq!(
+            provider_code("azure_translator", "pt-BR").as_deref(),
+            Some("pt")
+        );
+        assert_eq!(
+            provider_code("azure_translator", "sr").as_deref(),
+            Some("sr-Cyrl")
+        );
+        // Without a language list from the provider the tag is used
+        assert_eq!(provider_code("deepl", "en-GB").as_deref(), Some("en-GB"));
+
+        let mut catalogue = builtin_catalogue();
+        let reported = |code: &str, name: &str| ProviderLanguage {
+            code: code.to_string(),
+            name: name.to_string(),
+            native_name: None,
+        };
+        merge(
+            &mut catalogue,
+            "deepl",
+            &[
+                reported("EN-GB", "English (British)"),
+                reported("PT-PT", "Portuguese (European)"),
+                reported("EN", "English"),
+            ],
+        );
+        let code = |query: &str| {
+            position(&catalogue, query)
+                .and_then(|index| catalogue[index].providers.get("deepl").cloned())
+        };
+        assert_eq!(code("en-GB").as_deref(), Some("EN-GB"));
+        assert_eq!(code("pt-PT").as_deref(), Some("PT-PT"));
+        assert_eq!(code("en").as_deref(), Some("EN"));
+        assert_eq!(code("en-US"), None);
+    }
+
+    #[test]
+    fn test_only_names_migrate_to_tags() {
+        let migrate = |stored: &str| {
+            let mut value = stored.to_string();
+            migrate_name_to_tag(&mut value);
+            value
+        };
+        assert_eq!(migrate("Norwegian"), "nb");
+        assert_eq!(migrate("Chinese (Traditional)"), "zh-Hant");
+        assert_eq!(migrate("en-AU"), "en-AU");
+        assert_eq!(migrate("pt-BR"), "pt-BR");
+        assert_eq!(migrate("unknown"), "unknown");
+    }
+
+    #[test]
+    fn test_variants_share_a_base_language() {
+        assert_eq!(base_language("pt-BR"), base_language("pt-PT"));
+        assert_eq!(base_language("nb"), base_language("nn"));
+        assert_eq!(base_language("zh-Hant"), "zh");
+        assert_ne!(base_language("sr-Latn"), base_language("hr"));
+    }
+}
@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Manager, State};
+use tauri::{AppHandle, Emitter, EventTarget, Manager, State};
 use tauri_plugin_autostart::ManagerExt;
 use tauri_plugin_clipboard_manager::ClipboardExt;
 use tokio::sync::Mutex;
@@ -7,23 +7,40 @@ use tokio::sync::Mutex;
 #[cfg(target_os = "windows")]
 use winapi::um::winuser::{COLOR_WINDOW, GetSysColor};
 
+mod chunking;
+mod compare;
 mod config;
+mod failover;
 mod history;
+mod languages;
+mod models;
 mod provider_factory;
+mod retry;
+mod streaming;
 pub mod theme;
+mod trans_anthropic;
 mod trans_azure;
 mod trans_azure_translator;
+mod trans_deepl;
+mod trans_gemini;
+mod trans_libretranslate;
 mod trans_ollama;
 mod trans_openai;
 mod translation;
 mod tray;
 
+use compare::ComparisonResult;
 use config::Config;
 use history::{
     TranslationHistory, add_translation_to_history, clear_translation_history, deduplicate_history,
-    delete_history_entry, fix_target_language_in_history, get_translation_history,
+    delete_history_entry, get_translation_history,
+};
+use trans_ollama::{OllamaModelDetails, OllamaTranslationService};
+use translation::{
+    AlternativeTranslationsResult, ChunkSink, ProgressSink, ProviderCapabilities, TranslationChunk,
+    TranslationProgress, TranslationRequest, TranslationResponse, TranslationResult,
+    TranslationService,
 };
-use translation::{AlternativeTranslationsResult, TranslationResult, TranslationService};
 
 // Application state
 pub struct AppState {
@@ -84,8 +101,43 @@ async fn get_clipboard_text(app: AppHandle) -> Result<String, String> {
 // Now using only the translate function which has better duplicate detection
 
 #[tauri::command]
-async fn translate(text: String, config: State<'_, AppState>) -> Result<TranslationResult, String> {
-    match translation::translate_text(text, config).await {
+async fn translate(
+    text: String,
+    config: State<'_, AppState>,
+    window: tauri::WebviewWindow,
+) -> Result<TranslationResponse, String> {
+    // Forward partial output to the window that asked for the translation while the provider
+    // is streaming; cancellation is per window too
+    let chunk_window = window.clone();
+    let on_chunk: ChunkSink = Arc::new(move |partial: &str| {
+        let _ = chunk_window.emit_to(
+            EventTarget::webview_window(chunk_window.label()),
+            "translation-chunk",
+            TranslationChunk {
+                translated_text: partial.to_string(),
+            },
+        );
+    });
+
+    // Long texts are translated in chunks; report how many are done
+    let progress_window = window.clone();
+    let on_progress: ProgressSink = Arc::new(move |progress: TranslationProgress| {
+        let _ = progress_window.emit_to(
+            EventTarget::webview_window(progress_window.label()),
+            "translation-progress",
+            progress,
+        );
+    });
+
+    match translation::translate_text(
+        text,
+        window.label(),
+        config,
+        Some(on_chunk),
+        Some(on_progress),
+    )
+    .await
+    {
         Ok(response) => {
             // Add to history
             if let Err(e) = add_translation_to_history(
@@ -95,12 +147,13 @@ async fn translate(text: String, config: State<'_, AppState>) -> Result<Translat
                 response.target_language.clone(),
             ) {
                 log::error!("Failed to add translation to history: {}", e);
-            } // Convert TranslationResponse to TranslationResult for return
-            Ok(TranslationResult {
-                detected_language: response.detected_language,
-                translated_text: response.translated_text,
-                target_language: response.target_language,
-            })
+            }
+
+            Ok(response)
+        }
+        Err(translation::Error::Cancelled) => {
+            log::info!("Translation was cancelled by newer input");
+            Err("Translation cancelled".to_string())
         }
         Err(translation::Error::DuplicateRequest) => {
             // For duplicate requests, we'll just return an empty response
@@ -112,6 +165,74 @@ async fn translate(text: String, config: State<'_, AppState>) -> Result<Translat
     }
 }
 
+#[tauri::command]
+async fn cancel_translation(window: tauri::WebviewWindow) -> Result<(), String> {
+    translation::cancel_translation(window.label());
+    Ok(())
+}
+
+/// Translates `text` with several "provider:model" entries at once. Each result is also
+/// emitted as `comparison-result` as soon as its provider finishes.
+#[tauri::command]
+async fn compare_translations(
+    text: String,
+    entries: Vec<String>,
+    comparison_id: String,
+    state: State<'_, AppState>,
+    window: tauri::WebviewWindow,
+) -> Result<Vec<ComparisonResult>, String> {
+    if text.trim().is_empty() {
+        return Err("Nothing to translate".to_string());
+    }
+
+    let config = state.config.lock().await.clone();
+    let results =
+        compare::compare_translations(&text, &entries, &comparison_id, &config, |result| {
+            let _ = window.emit("comparison-result", result);
+        })
+        .await;
+    Ok(results)
+}
+
+/// Capabilities of `provider` (the configured provider when omitted), built from the saved config.
+#[tauri::command]
+async fn get_provider_capabilities(
+    provider: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ProviderCapabilities, String> {
+    let mut config = state.config.lock().await.clone();
+    if let Some(provider) = provider {
+        config.api_provider = provider;
+    }
+    Ok(provider_factory::create_provider(config).capabilities())
+}
+
+#[tauri::command]
+async fn get_language_catalogue() -> Result<Vec<languages::Language>, String> {
+    Ok(languages::catalogue())
+}
+
+/// Rebuilds the language catalogue in the background and sends the result to the frontend.
+fn refresh_language_catalogue(app: AppHandle, config: Config) {
+    tauri::async_runtime::spawn(async move {
+        languages::refresh(&config).await;
+        let _ = app.emit("language-catalogue-updated", languages::catalogue());
+    });
+}
+
+/// Loads the active Ollama model in the background when warm-up is enabled, so the first
+/// translation does not wait for the model to load.
+fn warm_up_ollama(config: Config) {
+    if !config.ollama_warmup || config.api_provider != "ollama" {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = OllamaTranslationService::new(config).warm_up().await {
+            log::warn!("Ollama warm-up failed: {}", e);
+        }
+    });
+}
+
 #[tauri::command]
 async fn get_config(state: State<'_, AppState>) -> Result<Config, String> {
     let config = state.config.lock().await;
@@ -131,6 +252,13 @@ async fn save_config(
     };
 
     let hotkey_changed = old_config.hotkey != new_config.hotkey;
+    let ollama_model_changed = new_config.ollama_warmup
+        && (!old_config.ollama_warmup
+            || old_config.api_provider != new_config.api_provider
+            || old_config.model != new_config.model
+            || old_config.ollama_url != new_config.ollama_url
+            || old_config.model_config("ollama", &old_config.model)
+                != new_config.model_config("ollama", &new_config.model));
 
     match new_config.save() {
         Ok(_) => {
@@ -142,6 +270,13 @@ async fn save_config(
             let mut service = state.translation_service.lock().await;
             *service = TranslationService::new(new_config.clone());
 
+            // Provider settings decide which language lists make up the catalogue
+            refresh_language_catalogue(app.clone(), new_config.clone());
+
+            if ollama_model_changed {
+                warm_up_ollama(new_config.clone());
+            }
+
             // Re-register global shortcut if hotkey changed
             if hotkey_changed && let Err(e) = setup_global_shortcut(&app, &new_config).await {
                 log::error!("Failed to update global shortcut: {}", e);
@@ -176,8 +311,9 @@ async fn test_translation_from_clipboard(
     }
 
     // Translate the text
+    let request = TranslationRequest::translate(text, &*state.config.lock().await);
     let service = state.translation_service.lock().await;
-    match service.detect_and_translate(&text).await {
+    match service.detect_and_translate(&request, None, None).await {
         Ok(result) => {
             log::info!(
                 "Translation test successful: {} -> {}",
@@ -197,9 +333,12 @@ async fn test_translation_from_clipboard(
 async fn get_alternative_translations(
     selected_text: String,
     target_language: String,
+    context: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<AlternativeTranslationsResult, String> {
-    match translation::get_alternative_translations(selected_text, target_language, state).await {
+    match translation::get_alternative_translations(selected_text, target_language, context, state)
+        .await
+    {
         Ok(result) => Ok(result),
         Err(e) => Err(e.to_string()),
     }
@@ -209,10 +348,16 @@ async fn get_alternative_translations(
 async fn get_alternative_translations_debug(
     selected_text: String,
     target_language: String,
+    context: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<serde_json::Value, String> {
-    match translation::get_alternative_translations_debug(selected_text, target_language, state)
-        .await
+    match translation::get_alternative_translations_debug(
+        selected_text,
+        target_language,
+        context,
+        state,
+    )
+    .await
     {
         Ok(result) => Ok(result),
         Err(e) => Err(e.to_string()),
@@ -252,6 +397,47 @@ async fn validate_api_key(
 
             Ok(response.status().is_success())
         }
+        "openai_compatible" => {
+            if let Some(base_url) = endpoint {
+                // Most OpenAI-compatible servers expose the model list next to chat completions
+                let url = format!("{}/models", base_url.trim().trim_end_matches('/'));
+
+                let mut request = client.get(&url);
+                if !api_key.is_empty() {
+                    request = request.header("Authorization", format!("Bearer {}", api_key));
+                }
+
+                let response = request
+                    .send()
+                    .await
+                    .map_err(|e| format!("Request failed: {}", e))?;
+
+                Ok(response.status().is_success())
+            } else {
+                Err("Base URL is required".to_string())
+            }
+        }
+        "anthropic" => {
+            let response = client
+                .get("https://api.anthropic.com/v1/models")
+                .header("x-api-key", &api_key)
+                .header("anthropic-version", "2023-06-01")
+                .send()
+                .await
+                .map_err(|e| format!("Request failed: {}", e))?;
+
+            Ok(response.status().is_success())
+        }
+        "gemini" => {
+            let response = client
+                .get("https://generativelanguage.googleapis.com/v1beta/models")
+                .header("x-goog-api-key", &api_key)
+                .send()
+                .await
+                .map_err(|e| format!("Request failed: {}", e))?;
+
+            Ok(response.status().is_success())
+        }
         "azure_openai" => {
             if let Some(endpoint) = endpoint {
                 // Use provided api_version, or try to extract from endpoint, or use default
@@ -335,10 +521,94 @@ async fn validate_api_key(
                 Err("Azure Translator endpoint is required".to_string())
             }
         }
+        "deepl" => {
+            // The usage endpoint is the cheapest authenticated call and costs no characters
+            let url = format!("{}/v2/usage", trans_deepl::deepl_base_url(&api_key));
+            let response = client
+                .get(&url)
+                .header(
+                    "Authorization",
+                    format!("DeepL-Auth-Key {}", api_key.trim()),
+                )
+                .send()
+                .await
+                .map_err(|e| format!("DeepL API request failed: {}", e))?;
+
+            Ok(response.status().is_success())
+        }
+        "libretranslate" => {
+            if let Some(endpoint) = endpoint {
+                // A detection call exercises both the server and the (optional) API key
+                let url = format!("{}/detect", endpoint.trim().trim_end_matches('/'));
+                let mut test_body = serde_json::json!({"q": "Hello"});
+                if !api_key.trim().is_empty() {
+                    test_body["api_key"] = serde_json::json!(api_key.trim());
+                }
+
+                let response = client
+                    .post(&url)
+                    .json(&test_body)
+                    .send()
+                    .await
+                    .map_err(|e| format!("LibreTranslate request failed: {}", e))?;
+
+                Ok(response.status().is_success())
+            } else {
+                Err("LibreTranslate URL is required".to_string())
+            }
+        }
         _ => Err("Unsupported API provider".to_string()),
     }
 }
 
+/// Lists the chat models a provider offers and merges them into the provider's model list in
+/// `config`, which may hold credentials that are not saved yet. The frontend saves the result.
+#[tauri::command]
+async fn discover_models(
+    config: Config,
+    provider: String,
+) -> Result<Vec<config::ModelConfig>, String> {
+    let discovered = models::discover(&config, &provider)
+        .await
+        .map_err(|e| format!("Failed to discover models: {}", e))?;
+
+    let mut provider_models = config
+        .available_models
+        .get(&provider)
+        .cloned()
+        .unwrap_or_default();
+    let added = models::merge(&mut provider_models, &provider, discovered);
+    log::info!("Added {} discovered models for {}", added, provider);
+    Ok(provider_models)
+}
+
+/// Downloads an Ollama model, emitting `ollama-pull-progress` while it runs.
+#[tauri::command]
+async fn pull_ollama_model(config: Config, model: String, app: AppHandle) -> Result<(), String> {
+    OllamaTranslationService::new(config)
+        .pull_model(&model, |progress| {
+            let _ = app.emit("ollama-pull-progress", progress);
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn delete_ollama_model(config: Config, model: String) -> Result<(), String> {
+    OllamaTranslationService::new(config)
+        .delete_model(&model)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn show_ollama_model(config: Config, model: String) -> Result<OllamaModelDetails, String> {
+    OllamaTranslationService::new(config)
+        .show_model(&model)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn get_translation_history_cmd() -> Result<TranslationHistory, String> {
     get_translation_history().map_err(|e| format!("Failed to get translation history: {}", e))
@@ -359,12 +629,6 @@ async fn delete_history_entry_cmd(entry_id: String) -> Result<(), String> {
     delete_history_entry(entry_id).map_err(|e| format!("Failed to delete history entry: {}", e))
 }
 
-#[tauri::command]
-async fn fix_target_language_in_history_cmd() -> Result<(), String> {
-    fix_target_language_in_history()
-        .map_err(|e| format!("Failed to fix target language in history: {}", e))
-}
-
 #[tauri::command]
 async fn reset_detected_language() -> Result<(), String> {
     log::info!("Detected language reset requested");
@@ -664,6 +928,9 @@ pub fn run() {
                 }
             });
 
+            refresh_language_catalogue(app.handle().clone(), config.clone());
+            warm_up_ollama(config.clone());
+
             // Setup autostart if enabled
             if config.auto_start {
                 let autostart = app.autolaunch();
@@ -678,20 +945,27 @@ pub fn run() {
             show_main_window,
             get_clipboard_text,
             translate,
+            cancel_translation,
             get_config,
             save_config,
             copy_to_clipboard,
             test_translation_from_clipboard,
             get_windows_theme,
             validate_api_key,
+            discover_models,
+            pull_ollama_model,
+            delete_ollama_model,
+            show_ollama_model,
             get_translation_history_cmd,
             clear_translation_history_cmd,
             deduplicate_history_cmd,
             delete_history_entry_cmd,
-            fix_target_language_in_history_cmd,
             reset_detected_language,
             get_alternative_translations,
-            get_alternative_translations_debug
+            get_alternative_translations_debug,
+            get_provider_capabilities,
+            get_language_catalogue,
+            compare_translations
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,277 @@
+use crate::config::{Config, ModelConfig};
+use crate::trans_azure::AzureOpenAITranslationService;
+use crate::trans_ollama::OllamaTranslationService;
+use crate::trans_openai::OpenAITranslationService;
+use anyhow::Result;
+
+/// An entry of a provider's own model list.
+#[derive(Debug, Clone)]
+pub struct ProviderModel {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+// Model ids containing one of these are not chat models: embeddings, speech, images, moderation
+// and the legacy completion models
+const NON_CHAT_MARKERS: &[&str] = &[
+    "embed",
+    "rerank",
+    "whisper",
+    "tts",
+    "transcribe",
+    "audio",
+    "realtime",
+    "dall-e",
+    "image",
+    "sora",
+    "moderation",
+    "search",
+    "computer-use",
+    "davinci",
+    "babbage",
+];
+
+/// Whether a model can serve chat completions, judged by its id.
+pub fn is_chat_model(name: &str) -> bool {
+    let name = name.to_lowercase();
+    !NON_CHAT_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+/// How a chat completions model is called. Settings the user left unset in the model list are
+/// filled in from the model name, so a new model family only needs its entry edited.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatModelSettings {
+    pub is_reasoning: bool,
+    pub system_role: String,
+    pub max_output_tokens: u32,
+    pub temperature: Option<f32>, // Reasoning models reject it unless the user sets one
+    pub reasoning_effort: Option<String>, // Only sent to reasoning models
+    pub verbosity: Option<String>, // Unset leaves the API default (medium)
+}
+
+impl ChatModelSettings {
+    /// Output tokens left for the reply. Reasoning tokens count against the same limit, so
+    /// reasoning models are assumed to spend half of it thinking.
+    pub fn reply_tokens(&self) -> u32 {
+        if self.is_reasoning {
+            self.max_output_tokens / 2
+        } else {
+            self.max_output_tokens
+        }
+    }
+}
+
+const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 800;
+const DEFAULT_TEMPERATURE: f32 = 0.3;
+
+/// Whether the name belongs to a known reasoning model family: gpt-5, the o-series and codex-mini.
+pub fn is_reasoning_model_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.starts_with("gpt-5")
+        || name.starts_with("gpt5")
+        || is_o_series(&name)
+        || name.starts_with("codex-mini")
+}
+
+// o1, o3-mini, o4-mini, ...
+fn is_o_series(name: &str) -> bool {
+    name.strip_prefix('o')
+        .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// The settings for `model` of `provider`: its model list entry, with defaults for unset fields.
+pub fn chat_settings(config: &Config, provider: &str, model: &str) -> ChatModelSettings {
+    let entry = config.model_config(provider, model);
+    let is_reasoning = entry
+        .and_then(|m| m.is_reasoning)
+        .unwrap_or_else(|| is_reasoning_model_name(model));
+    // The o-series only accepts instructions in the developer role
+    let system_role = entry
+        .and_then(|m| m.system_role.clone())
+        .unwrap_or_else(|| {
+            if is_o_series(&model.to_lowercase()) {
+                "developer".to_string()
+            } else {
+                "system".to_string()
+            }
+        });
+    let temperature = entry
+        .and_then(|m| m.temperature)
+        .or((!is_reasoning).then_some(DEFAULT_TEMPERATURE));
+    let reasoning_effort = is_reasoning.then(|| {
+        entry
+            .and_then(|m| m.reasoning_effort.clone())
+            .or_else(|| config.reasoning_effort.clone())
+            .unwrap_or_else(|| "low".to_string())
+    });
+
+    ChatModelSettings {
+        is_reasoning,
+        system_role,
+        max_output_tokens: entry
+            .and_then(|m| m.max_output_tokens)
+            .unwrap_or(DEFAULT_MAX_OUTPUT_TOKENS),
+        temperature,
+        reasoning_effort,
+        verbosity: entry.and_then(|m| m.verbosity.clone()),
+    }
+}
+
+/// Lists the chat models `provider` offers with the credentials in `config`.
+pub async fn discover(config: &Config, provider: &str) -> Result<Vec<ProviderModel>> {
+    let models = match provider {
+        "openai" => {
+            OpenAITranslationService::new(config.clone())
+                .list_models()
+                .await?
+        }
+        "openai_compatible" => {
+            OpenAITranslationService::new_compatible(config.clone())
+                .list_models()
+                .await?
+        }
+        "azure_openai" => {
+            AzureOpenAITranslationService::new(config.clone())
+                .list_deployments()
+                .await?
+        }
+        "ollama" => {
+            OllamaTranslationService::new(config.clone())
+                .list_models()
+                .await?
+        }
+        other => {
+            return Err(anyhow::anyhow!(
+                "Model discovery is not supported for '{}'",
+                other
+            ));
+        }
+    };
+
+    let total = models.len();
+    let chat_models: Vec<ProviderModel> = models
+        .into_iter()
+        .filter(|model| is_chat_model(&model.name))
+        .collect();
+    log::info!(
+        "Discovered {} chat models for {} ({} listed)",
+        chat_models.len(),
+        provider,
+        total
+    );
+    Ok(chat_models)
+}
+
+/// Adds discovered models to a provider's model list. Models already in the list keep the
+/// user's settings and only gain a missing description. New models are added disabled, so a
+/// provider listing dozens of models does not flood the model picker. Returns how many were added.
+pub fn merge(
+    existing: &mut Vec<ModelConfig>,
+    provider: &str,
+    discovered: Vec<ProviderModel>,
+) -> usize {
+    let mut added = 0;
+    for model in discovered {
+        match existing.iter_mut().find(|m| m.name == model.name) {
+            Some(known) => {
+                if known.description.is_none() {
+                    known.description = model.description;
+                }
+            }
+            None => {
+                existing.push(ModelConfig {
+                    display_name: model.name.clone(),
+                    name: model.name,
+                    provider: provider.to_string(),
+                    is_enabled: false,
+                    description: model.description,
+                    ..Default::default()
+                });
+                added += 1;
+            }
+        }
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_chat_models_are_filtered() {
+        assert!(is_chat_model("gpt-4o-mini"));
+        assert!(is_chat_model("o3-mini"));
+        assert!(is_chat_model("llama3.2:3b"));
+        assert!(!is_chat_model("text-embedding-3-small"));
+        assert!(!is_chat_model("nomic-embed-text:latest"));
+        assert!(!is_chat_model("whisper-1"));
+        assert!(!is_chat_model("gpt-4o-mini-tts"));
+        assert!(!is_chat_model("dall-e-3"));
+    }
+
+    fn config_with(model: ModelConfig) -> Config {
+        let mut config = Config {
+            reasoning_effort: Some("medium".to_string()),
+            ..Default::default()
+        };
+        config
+            .available_models
+            .insert("openai".to_string(), vec![model]);
+        config
+    }
+
+    #[test]
+    fn test_chat_settings_defaults_come_from_the_name() {
+        let config = Config::default();
+
+        let chat = chat_settings(&config, "openai", "gpt-4o-mini");
+        assert!(!chat.is_reasoning);
+        assert_eq!(chat.system_role, "system");
+        assert_eq!(chat.temperature, Some(0.3));
+        assert_eq!(chat.reasoning_effort, None);
+        assert_eq!(chat.verbosity, None);
+
+        let o_series = chat_settings(&config, "openai", "o5-preview");
+        assert!(o_series.is_reasoning);
+        assert_eq!(o_series.system_role, "developer");
+        assert_eq!(o_series.temperature, None);
+
+        assert_eq!(
+            chat_settings(&config, "openai", "gpt-5-mini").system_role,
+            "system"
+        );
+    }
+
+    #[test]
+    fn test_chat_settings_entry_overrides_defaults() {
+        let config = config_with(ModelConfig {
+            name: "my-reasoner".to_string(),
+            is_reasoning: Some(true),
+            system_role: Some("developer".to_string()),
+            max_output_tokens: Some(4000),
+            verbosity: Some("low".to_string()),
+            ..Default::default()
+        });
+        let settings = chat_settings(&config, "openai", "my-reasoner");
+        assert!(settings.is_reasoning);
+        assert_eq!(settings.system_role, "developer");
+        assert_eq!(settings.max_output_tokens, 4000);
+        assert_eq!(settings.reply_tokens(), 2000);
+        assert_eq!(settings.temperature, None);
+        assert_eq!(settings.reasoning_effort.as_deref(), Some("medium"));
+        assert_eq!(settings.verbosity.as_deref(), Some("low"));
+
+        let config = config_with(ModelConfig {
+            name: "o3-mini".to_string(),
+            is_reasoning: Some(false),
+            temperature: Some(0.7),
+            reasoning_effort: Some("high".to_string()),
+            ..Default::default()
+        });
+        let settings = chat_settings(&config, "openai", "o3-mini");
+        assert!(!settings.is_reasoning);
+        assert_eq!(settings.temperature, Some(0.7));
+        assert_eq!(settings.reasoning_effort, None);
+    }
+}
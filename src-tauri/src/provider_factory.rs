@@ -1,9 +1,14 @@
 use crate::config::Config;
+use crate::trans_anthropic::AnthropicTranslationService;
 use crate::trans_azure::AzureOpenAITranslationService;
 use crate::trans_azure_translator::AzureTranslatorService;
+use crate::trans_deepl::DeepLTranslationService;
+use crate::trans_gemini::GeminiTranslationService;
+use crate::trans_libretranslate::LibreTranslateService;
 use crate::trans_ollama::OllamaTranslationService;
 use crate::trans_openai::OpenAITranslationService;
-use crate::translation::TranslationProvider;
+use crate::translation::{ProviderCapabilities, TranslationProvider, TranslationRequest};
+use anyhow::Result;
 
 /// Central place to construct providers so adding new ones touches fewer files.
 pub fn create_provider(mut config: Config) -> Box<dyn TranslationProvider + Send + Sync> {
@@ -12,8 +17,13 @@ pub fn create_provider(mut config: Config) -> Box<dyn TranslationProvider + Send
 
     match config.api_provider.as_str() {
         "openai" => Box::new(OpenAITranslationService::new(config)),
+        "openai_compatible" => Box::new(OpenAITranslationService::new_compatible(config)),
+        "anthropic" => Box::new(AnthropicTranslationService::new(config)),
+        "gemini" => Box::new(GeminiTranslationService::new(config)),
         "azure_openai" => Box::new(AzureOpenAITranslationService::new(config)),
         "azure_translator" => Box::new(AzureTranslatorService::new(config)),
+        "deepl" => Box::new(DeepLTranslationService::new(config)),
+        "libretranslate" => Box::new(LibreTranslateService::new(config)),
         "ollama" => Box::new(OllamaTranslationService::new(config)),
         other => {
             log::warn!("Unknown API provider '{}' , defaulting to OpenAI", other);
@@ -23,3 +33,65 @@ pub fn create_provider(mut config: Config) -> Box<dyn TranslationProvider + Send
         }
     }
 }
+
+fn provider_display_name(provider: &str) -> &str {
+    match provider {
+        "azure_translator" => "Azure Translator",
+        "deepl" => "DeepL",
+        "libretranslate" => "LibreTranslate",
+        other => other,
+    }
+}
+
+/// Whether alternatives for `request` go to the configured fallback provider: the current
+/// provider cannot generate any, or only handles single words and a fallback is configured
+/// for phrases. Without a fallback, word-only providers still try phrases themselves.
+pub fn uses_alternatives_fallback(
+    capabilities: &ProviderCapabilities,
+    request: &TranslationRequest,
+    config: &Config,
+) -> bool {
+    if !capabilities.alternatives {
+        return true;
+    }
+    let is_phrase = request.text.split_whitespace().count() > 1;
+    is_phrase && !capabilities.phrase_alternatives && config.parse_alternatives_fallback().is_some()
+}
+
+/// Provider for alternatives requests: the current provider, or the configured fallback
+/// provider and model when the current one cannot generate alternatives.
+pub fn create_alternatives_provider(
+    mut config: Config,
+    request: &TranslationRequest,
+) -> Result<Box<dyn TranslationProvider + Send + Sync>> {
+    let provider = create_provider(config.clone());
+    if !uses_alternatives_fallback(&provider.capabilities(), request, &config) {
+        log::info!(
+            "Using current provider '{}' for alternatives",
+            config.api_provider
+        );
+        return Ok(provider);
+    }
+
+    let Some((fallback_provider, model)) = config.parse_alternatives_fallback() else {
+        return Err(anyhow::anyhow!(
+            "{} cannot generate alternatives. Please configure a fallback provider in settings.",
+            provider_display_name(&config.api_provider)
+        ));
+    };
+
+    log::info!(
+        "Using fallback provider '{}' with model '{}' for alternatives",
+        fallback_provider,
+        model
+    );
+
+    // For Azure OpenAI, ensure deployment name matches model name
+    if fallback_provider == "azure_openai" {
+        config.azure_deployment_name = model.clone();
+    }
+    config.api_provider = fallback_provider;
+    config.model = model;
+
+    Ok(create_provider(config))
+}
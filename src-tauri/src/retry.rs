@@ -0,0 +1,271 @@
+use crate::config::Config;
+use anyhow::Result;
+use reqwest::header::HeaderMap;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(8);
+
+/// How often, and for how long, provider requests are retried after rate limits and
+/// transient failures.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub deadline: Duration, // Total time budget across all attempts, including waits
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_attempts: config.retry_max_attempts.max(1),
+            deadline: Duration::from_secs(config.retry_deadline_secs),
+        }
+    }
+
+    /// Exponential backoff with "equal jitter": at least half the delay is always waited,
+    /// so retries from several windows spread out without hammering the API.
+    fn backoff(&self, failed_attempt: u32) -> Duration {
+        let exponential = INITIAL_BACKOFF.saturating_mul(1 << (failed_attempt - 1).min(16));
+        let half = exponential.min(MAX_BACKOFF) / 2;
+        half + half.mul_f64(random_fraction())
+    }
+
+    /// Delay before the next attempt, or None once the attempts or the deadline are used up.
+    /// A wait requested by the server takes precedence over the computed backoff.
+    fn next_delay(
+        &self,
+        failed_attempt: u32,
+        started: Instant,
+        server_delay: Option<Duration>,
+    ) -> Option<Duration> {
+        if failed_attempt >= self.max_attempts {
+            return None;
+        }
+        let delay = server_delay.unwrap_or_else(|| self.backoff(failed_attempt));
+        if started.elapsed() + delay > self.deadline {
+            return None;
+        }
+        Some(delay)
+    }
+}
+
+fn random_fraction() -> f64 {
+    // Every RandomState is seeded randomly, which is plenty for jitter
+    let hash = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Rate limits, timeouts and overloaded or unavailable servers are worth another attempt.
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    matches!(status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504 | 529)
+}
+
+fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect()
+}
+
+/// Wait requested by the server through `retry-after-ms`, `Retry-After` (seconds or an HTTP
+/// date) or the `x-ratelimit-reset*` headers.
+fn server_retry_delay(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+
+    if let Some(millis) = header("retry-after-ms").and_then(|v| v.parse::<f64>().ok()) {
+        return Duration::try_from_secs_f64(millis / 1000.0).ok();
+    }
+
+    if let Some(value) = header("retry-after") {
+        if let Ok(seconds) = value.parse::<f64>() {
+            return Duration::try_from_secs_f64(seconds).ok();
+        }
+        if let Ok(date) = chrono::DateTime::parse_from_rfc2822(value) {
+            return (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                .to_std()
+                .ok();
+        }
+    }
+
+    // OpenAI reports separate request and token windows; wait for the later one
+    [
+        "x-ratelimit-reset-requests",
+        "x-ratelimit-reset-tokens",
+        "x-ratelimit-reset",
+    ]
+    .into_iter()
+    .filter_map(|name| header(name).and_then(parse_reset))
+    .max()
+}
+
+/// Parses a rate limit reset value: a duration like "1m30s" or "20ms", a number of seconds,
+/// or a Unix timestamp.
+fn parse_reset(value: &str) -> Option<Duration> {
+    if let Ok(number) = value.parse::<f64>() {
+        if number > 1_000_000_000.0 {
+            let now = chrono::Utc::now().timestamp() as f64;
+            return Duration::try_from_secs_f64(number - now).ok();
+        }
+        return Duration::try_from_secs_f64(number).ok();
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .filter(|&idx| idx > 0)?;
+        let (number, tail) = rest.split_at(split);
+        let number: f64 = number.parse().ok()?;
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let seconds = match unit {
+            "ms" => number / 1000.0,
+            "s" => number,
+            "m" => number * 60.0,
+            "h" => number * 3600.0,
+            _ => return None,
+        };
+        total += Duration::try_from_secs_f64(seconds).ok()?;
+        rest = tail;
+    }
+    Some(total)
+}
+
+/// Sends the request built by `build_request`, retrying rate limits, transient server errors
+/// and connection failures. The last response is returned whether it succeeded or not, so
+/// callers keep reporting error statuses themselves.
+pub async fn send_with_retry(
+    policy: RetryPolicy,
+    provider: &str,
+    build_request: impl Fn() -> reqwest::RequestBuilder,
+) -> Result<reqwest::Response> {
+    let started = Instant::now();
+    let mut attempt = 1;
+    loop {
+        let delay = match build_request().send().await {
+            Ok(response) if is_retryable_status(response.status()) => {
+                let status = response.status();
+                let server_delay = server_retry_delay(response.headers());
+                let Some(delay) = policy.next_delay(attempt, started, server_delay) else {
+                    if attempt > 1 {
+                        log::warn!(
+                            "{} request still failing with {} after {} attempts, giving up",
+                            provider,
+                            status,
+                            attempt
+                        );
+                    }
+                    return Ok(response);
+                };
+                log::warn!(
+                    "{} request failed with {} (attempt {}/{}), retrying in {} ms{}",
+                    provider,
+                    status,
+                    attempt,
+                    policy.max_attempts,
+                    delay.as_millis(),
+                    if server_delay.is_some() {
+                        " as requested by the server"
+                    } else {
+                        ""
+                    }
+                );
+                delay
+            }
+            Ok(response) => {
+                if attempt > 1 {
+                    log::info!(
+                        "{} request completed on attempt {} with {}",
+                        provider,
+                        attempt,
+                        response.status()
+                    );
+                }
+                return Ok(response);
+            }
+            Err(e) if is_retryable_error(&e) => {
+                let Some(delay) = policy.next_delay(attempt, started, None) else {
+                    return Err(e.into());
+                };
+                log::warn!(
+                    "{} request failed: {} (attempt {}/{}), retrying in {} ms",
+                    provider,
+                    e,
+                    attempt,
+                    policy.max_attempts,
+                    delay.as_millis()
+                );
+                delay
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Same policy for clients that do not expose the HTTP response (ollama-rs). Only errors
+/// for which `is_transient` returns true are retried, using the computed backoff.
+pub async fn retry<T, E, Fut>(
+    policy: RetryPolicy,
+    provider: &str,
+    is_transient: impl Fn(&E) -> bool,
+    operation: impl Fn() -> Fut,
+) -> std::result::Result<T, E>
+where
+    E: std::fmt::Display,
+    Fut: Future<Output = std::result::Result<T, E>>,
+{
+    let started = Instant::now();
+    let mut attempt = 1;
+    loop {
+        match operation().await {
+            Ok(value) => {
+                if attempt > 1 {
+                    log::info!("{} request completed on attempt {}", provider, attempt);
+                }
+                return Ok(value);
+            }
+            Err(e) if is_transient(&e) => {
+                let Some(delay) = policy.next_delay(attempt, started, None) else {
+                    return Err(e);
+                };
+                log::warn!(
+                    "{} request failed: {} (attempt {}/{}), retrying in {} ms",
+                    provider,
+                    e,
+                    attempt,
+                    policy.max_attempts,
+                    delay.as_millis()
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reset() {
+        assert_eq!(parse_reset("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_reset("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse_reset("20ms"), Some(Duration::from_millis(20)));
+        assert_eq!(parse_reset("1.5"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_reset("soon"), None);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::translation::{ChunkSink, truncated_reply_error};
+use anyhow::Result;
+use futures_util::StreamExt;
+use serde_json::Value;
+
+/// Reads an OpenAI-style chat completion SSE stream (`stream: true`) and returns the
+/// concatenated message content once the stream ends. OpenAI and Azure OpenAI both use
+/// this protocol. Every time the partial `translated_text` value grows, it is forwarded
+/// to `on_chunk` so the UI can render the translation while it is still being generated.
+pub async fn read_chat_completion_stream(
+    response: reqwest::Response,
+    on_chunk: &ChunkSink,
+) -> Result<String> {
+    read_sse_stream(response, on_chunk, extract_delta_content, is_length_finish).await
+}
+
+/// Generic SSE reader; `extract_text` pulls the generated text out of a single `data:` event
+/// and `is_truncated` tells whether an event reports that the output token limit was reached.
+/// A stream that ends because of the output token limit is an error.
+pub async fn read_sse_stream(
+    response: reqwest::Response,
+    on_chunk: &ChunkSink,
+    extract_text: fn(&Value) -> Option<&str>,
+    is_truncated: fn(&Value) -> bool,
+) -> Result<String> {
+    let mut stream = response.bytes_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut content = String::new();
+    let mut last_emitted = String::new();
+    let mut truncated = false;
+
+    'outer: while let Some(bytes) = stream.next().await {
+        let bytes = bytes.map_err(|e| anyhow::anyhow!("Failed to read stream: {}", e))?;
+        buffer.extend_from_slice(&bytes);
+
+        // SSE events are line based; only process complete lines and keep the remainder
+        while let Some(newline_idx) = buffer.iter().position(|b| *b == b'\n') {
+            let line_bytes: Vec<u8> = buffer.drain(..=newline_idx).collect();
+            let line = String::from_utf8_lossy(&line_bytes);
+            let line = line.trim();
+
+            let Some(data) = line.strip_prefix("data:") else {
+                // Ignore comments, event names and keep-alive blank lines
+                continue;
+            };
+            let data = data.trim();
+            if data == "[DONE]" {
+                break 'outer;
+            }
+
+            let event: Value = match serde_json::from_str(data) {
+                Ok(event) => event,
+                Err(e) => {
+                    log::warn!("Skipping malformed stream event: {} ({})", data, e);
+                    continue;
+                }
+            };
+
+            if let Some(error) = event.get("error") {
+                return Err(anyhow::anyhow!("Stream returned an error: {}", error));
+            }
+            truncated |= is_truncated(&event);
+
+            if let Some(delta) = extract_text(&event) {
+                content.push_str(delta);
+
+                if let Some(partial) = extract_partial_json_string(&content, "translated_text")
+                    && partial != last_emitted
+                {
+                    on_chunk(&partial);
+                    last_emitted = partial;
+                }
+            }
+        }
+    }
+
+    log::info!("Stream finished, received {} characters", content.len());
+    if truncated {
+        log::warn!("Stream was cut off at the output token limit");
+        return Err(truncated_reply_error());
+    }
+    Ok(content)
+}
+
+fn extract_delta_content(event: &Value) -> Option<&str> {
+    event
+        .get("choices")?
+        .as_array()?
+        .first()?
+        .get("delta")?
+        .get("content")?
+        .as_str()
+}
+
+fn is_length_finish(event: &Value) -> bool {
+    event["choices"][0]["finish_reason"].as_str() == Some("length")
+}
+
+/// Extracts the (possibly unterminated) string value of `key` from a JSON document that is
+/// still being generated. Returns `None` until the opening quote of the value has arrived.
+pub fn extract_partial_json_string(buffer: &str, key: &str) -> Option<String> {
+    let needle = format!("\"{}\"", key);
+    let key_idx = buffer.find(&needle)?;
+    let rest = buffer[key_idx + needle.len()..].trim_start();
+    let rest = rest.strip_prefix(':')?.trim_start();
+    let rest = rest.strip_prefix('"')?;
+
+    let mut value = String::new();
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some('r') | Some('b') | Some('f') => {}
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    if hex.len() < 4 {
+                        // Escape sequence is cut off at the end of the buffer
+                        return Some(value);
+                    }
+                    if let Some(ch) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                        value.push(ch);
+                    }
+                }
+                Some(other) => value.push(other),
+                None => return Some(value),
+            },
+            _ => value.push(c),
+        }
+    }
+
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_partial_json_string() {
+        assert_eq!(
+            extract_partial_json_string("{\"detected_language\": \"Eng", "translated_text"),
+            None
+        );
+        assert_eq!(
+            extract_partial_json_string(
+                "{\"detected_language\": \"English\", \"translated_text\": \"Hola\\nmun",
+                "translated_text"
+            ),
+            Some("Hola\nmun".to_string())
+        );
+        assert_eq!(
+            extract_partial_json_string(
+                "{\"translated_text\": \"Sie sagte \\\"Hallo\\\"\", \"x\": 1}",
+                "translated_text"
+            ),
+            Some("Sie sagte \"Hallo\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_length_finish_marks_truncation() {
+        let event = |finish_reason: Value| {
+            serde_json::json!({
+                "choices": [{"delta": {}, "finish_reason": finish_reason}]
+            })
+        };
+        assert!(is_length_finish(&event("length".into())));
+        assert!(!is_length_finish(&event("stop".into())));
+        assert!(!is_length_finish(&event(Value::Null)));
+    }
+}
@@ -0,0 +1,304 @@
+use crate::chunking;
+use crate::config::Config;
+use crate::languages;
+use crate::models::{self, ChatModelSettings};
+use crate::retry::{RetryPolicy, send_with_retry};
+use crate::streaming::read_sse_stream;
+use crate::translation::{
+    ChunkSink, ProviderCapabilities, ProviderError, TranslationProvider, TranslationRequest,
+    TranslationResult, clean_text_for_translation, create_alternatives_prompt, create_smart_prompt,
+    parse_translation_json, truncated_reply_error,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+const ANTHROPIC_MESSAGES_URL: &str = "https://api.anthropic.com/v1/messages";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+pub struct AnthropicTranslationService {
+    client: reqwest::Client,
+    config: Config,
+}
+
+impl AnthropicTranslationService {
+    pub fn new(config: Config) -> Self {
+        log::info!(
+            "Creating AnthropicTranslationService with model: {}",
+            config.model
+        );
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    fn build_request_body(&self, request: &TranslationRequest) -> Result<Value> {
+        let cleaned_text = clean_text_for_translation(&request.text);
+        log::info!("Cleaned text for translation: {}", cleaned_text);
+
+        if self.config.model.trim().is_empty() {
+            log::error!("AnthropicTranslationService: model is empty; cannot proceed");
+            return Err(ProviderError::not_configured(
+                "Model not configured for Anthropic provider",
+            ));
+        }
+
+        let is_alternatives_request = request.is_alternatives();
+
+        let (user_prompt, system_prompt) = if is_alternatives_request {
+            log::info!("Using alternatives prompt");
+            (
+                format!("\"{}\"", cleaned_text),
+                create_alternatives_prompt(request),
+            )
+        } else {
+            let user_prompt = format!(
+                "Text to translate into {}: \"{}\"",
+                languages::english_name(&request.target_language),
+                cleaned_text
+            );
+            let smart_prompt = create_smart_prompt(&self.config, request);
+            // The Messages API has no JSON mode, so the output format is spelled out explicitly
+            let system_prompt = format!(
+                "{}\n\nIMPORTANT FORMATTING RULES:\n- Respond with a single valid JSON object containing 'detected_language' and 'translated_text' fields and nothing else\n- Do not wrap the JSON in code fences or add any commentary\n- Preserve line breaks and paragraph structure in the translation\n\nExample response format:\n{{\n  \"detected_language\": \"English\",\n  \"translated_text\": \"Line 1\\nLine 2\\n\\nNew paragraph\"\n}}",
+                smart_prompt
+            );
+            (user_prompt, system_prompt)
+        };
+
+        let settings = self.chat_settings();
+        let mut request_body = json!({
+            "model": self.config.model,
+            "max_tokens": settings.max_output_tokens,
+            "system": system_prompt,
+            "messages": [
+                {
+                    "role": "user",
+                    "content": user_prompt
+                }
+            ]
+        });
+        if let Some(temperature) = settings.temperature {
+            request_body["temperature"] = json!(temperature);
+        }
+        Ok(request_body)
+    }
+
+    fn chat_settings(&self) -> ChatModelSettings {
+        models::chat_settings(&self.config, "anthropic", &self.config.model)
+    }
+
+    fn request(&self, request_body: &Value) -> reqwest::RequestBuilder {
+        self.client
+            .post(ANTHROPIC_MESSAGES_URL)
+            .header("x-api-key", &self.config.anthropic_api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .header("Content-Type", "application/json")
+            .json(request_body)
+    }
+
+    async fn call_anthropic(&self, request_body: Value) -> Result<Value> {
+        log::info!("Making Anthropic request to: {}", ANTHROPIC_MESSAGES_URL);
+        log::info!(
+            "Request body: {}",
+            serde_json::to_string_pretty(&request_body).unwrap_or_default()
+        );
+
+        let response = send_with_retry(RetryPolicy::from_config(&self.config), "Anthropic", || {
+            self.request(&request_body)
+        })
+        .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            log::error!(
+                "Anthropic API request failed: Status: {}, Error: {}",
+                status,
+                error_text
+            );
+            return Err(ProviderError::status(
+                status,
+                format!("Anthropic API request failed ({}): {}", status, error_text),
+            ));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    async fn call_anthropic_stream(&self, mut request_body: Value) -> Result<reqwest::Response> {
+        request_body["stream"] = json!(true);
+        log::info!(
+            "Making streaming Anthropic request to: {}",
+            ANTHROPIC_MESSAGES_URL
+        );
+
+        let response = send_with_retry(RetryPolicy::from_config(&self.config), "Anthropic", || {
+            self.request(&request_body)
+        })
+        .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            log::error!(
+                "Anthropic streaming request failed: Status: {}, Error: {}",
+                status,
+                error_text
+            );
+            return Err(ProviderError::status(
+                status,
+                format!("Anthropic API request failed ({}): {}", status, error_text),
+            ));
+        }
+
+        Ok(response)
+    }
+
+    fn extract_text_content(response: &Value) -> Result<String> {
+        let blocks = response["content"].as_array().ok_or_else(|| {
+            anyhow::anyhow!(
+                "No 'content' array in response. Full response: {}",
+                serde_json::to_string_pretty(response).unwrap_or_default()
+            )
+        })?;
+
+        if is_max_tokens_stop(response) {
+            log::warn!("Anthropic response was cut off at max_tokens");
+            return Err(truncated_reply_error());
+        }
+
+        // Responses are a list of content blocks; only text blocks carry the answer
+        let content = blocks
+            .iter()
+            .filter(|block| block["type"].as_str() == Some("text"))
+            .filter_map(|block| block["text"].as_str())
+            .collect::<String>();
+
+        if content.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No text content in response. Full response: {}",
+                serde_json::to_string_pretty(response).unwrap_or_default()
+            ));
+        }
+
+        Ok(content)
+    }
+}
+
+fn extract_text_delta(event: &Value) -> Option<&str> {
+    // Only `content_block_delta` events of type `text_delta` contain generated text
+    if event["type"].as_str() != Some("content_block_delta") {
+        return None;
+    }
+    let delta = event.get("delta")?;
+    if delta["type"].as_str() != Some("text_delta") {
+        return None;
+    }
+    delta["text"].as_str()
+}
+
+// The stop reason comes with the message itself, or with the closing `message_delta` event of a
+// stream
+fn is_max_tokens_stop(event: &Value) -> bool {
+    let stop_reason = match event["type"].as_str() {
+        Some("message_delta") => &event["delta"]["stop_reason"],
+        _ => &event["stop_reason"],
+    };
+    stop_reason.as_str() == Some("max_tokens")
+}
+
+#[async_trait]
+impl TranslationProvider for AnthropicTranslationService {
+    fn capabilities(&self) -> ProviderCapabilities {
+        let settings = self.chat_settings();
+        ProviderCapabilities {
+            alternatives: true,
+            phrase_alternatives: true,
+            streaming: true,
+            language_detection: true,
+            max_input_chars: None,
+            max_chunk_chars: Some(chunking::chars_for_output_tokens(settings.reply_tokens())),
+            transliteration: true,
+            structured_output: false,
+            reasoning_effort: false,
+        }
+    }
+
+    async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResult> {
+        let request_body = self.build_request_body(request)?;
+
+        log::info!("Using Anthropic model: {}", self.config.model);
+
+        let response = self.call_anthropic(request_body).await?;
+        let content = Self::extract_text_content(&response)?;
+        log::info!("API Response content: {}", content);
+
+        if request.is_alternatives() {
+            // For alternatives requests, return the raw content as translated_text
+            // The calling code will parse the JSON alternatives from it
+            log::info!("Returning raw alternatives response");
+            Ok(TranslationResult {
+                detected_language: "unknown".to_string(),
+                translated_text: content,
+                target_language: "alternatives".to_string(),
+                transliteration: None,
+            })
+        } else {
+            Ok(parse_translation_json(&content, &request.target_language))
+        }
+    }
+
+    async fn translate_streaming(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: ChunkSink,
+    ) -> Result<TranslationResult> {
+        // Alternatives are parsed as a whole by the caller, so there is nothing to stream
+        if request.is_alternatives() {
+            return self.translate(request).await;
+        }
+
+        let request_body = self.build_request_body(request)?;
+        log::info!("Streaming with Anthropic model: {}", self.config.model);
+
+        let response = self.call_anthropic_stream(request_body).await?;
+        let content =
+            read_sse_stream(response, &on_chunk, extract_text_delta, is_max_tokens_stop).await?;
+        if content.is_empty() {
+            return Err(anyhow::anyhow!("Empty streamed response from Anthropic"));
+        }
+
+        Ok(parse_translation_json(&content, &request.target_language))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_tokens_stop_is_detected_in_messages_and_streams() {
+        assert!(is_max_tokens_stop(&json!({
+            "type": "message",
+            "stop_reason": "max_tokens"
+        })));
+        assert!(!is_max_tokens_stop(&json!({
+            "type": "message",
+            "stop_reason": "end_turn"
+        })));
+        assert!(is_max_tokens_stop(&json!({
+            "type": "message_delta",
+            "delta": {"stop_reason": "max_tokens", "stop_sequence": null}
+        })));
+        assert!(!is_max_tokens_stop(&json!({
+            "type": "message_delta",
+            "delta": {"stop_reason": "end_turn", "stop_sequence": null}
+        })));
+        assert!(!is_max_tokens_stop(&json!({
+            "type": "content_block_delta",
+            "delta": {"type": "text_delta", "text": "max_tokens"}
+        })));
+    }
+}
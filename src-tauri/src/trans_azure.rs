@@ -1,6 +1,13 @@
+use crate::chunking;
 use crate::config::Config;
+use crate::languages;
+use crate::models::{self, ChatModelSettings, ProviderModel};
+use crate::retry::{RetryPolicy, send_with_retry};
+use crate::streaming::read_chat_completion_stream;
 use crate::translation::{
-    TranslationProvider, TranslationResult, clean_text_for_translation, create_smart_prompt,
+    ChunkSink, ProviderCapabilities, ProviderError, TranslationProvider, TranslationRequest,
+    TranslationResult, clean_text_for_translation, create_alternatives_prompt, create_smart_prompt,
+    json_schema_response_format, parse_translation_json, truncated_reply_error,
 };
 use anyhow::Result;
 use async_trait::async_trait;
@@ -23,35 +30,31 @@ impl AzureOpenAITranslationService {
         }
     }
 
-    fn is_reasoning_model(&self) -> bool {
-        // Automatic detection based on known reasoning model name patterns
-        // Supports custom Azure deployment names that include underlying model name
-        let deployment_name = if !self.config.azure_deployment_name.is_empty() {
-            self.config.azure_deployment_name.as_str()
+    /// The deployment requests address, which is also its name in the model list.
+    fn model_name(&self) -> &str {
+        if !self.config.azure_deployment_name.is_empty() {
+            &self.config.azure_deployment_name
         } else {
-            ""
-        };
-        let model_name = self.config.model.as_str();
-
-        // Check both deployment name and model name
-        let is_reasoning =
-            is_reasoning_model_name(deployment_name) || is_reasoning_model_name(model_name);
+            &self.config.model
+        }
+    }
 
-        log::info!(
-            "Azure - Reasoning model detection: deployment='{}', model='{}', is_reasoning={}",
-            deployment_name,
-            model_name,
-            is_reasoning
-        );
+    fn chat_settings(&self) -> ChatModelSettings {
+        models::chat_settings(&self.config, "azure_openai", self.model_name())
+    }
 
-        is_reasoning
+    /// Structured outputs need a Cognitive Services endpoint and api-version
+    /// 2024-08-01-preview or later; the Models API also serves non-OpenAI models.
+    fn supports_structured_output(&self) -> bool {
+        !self.config.azure_endpoint.contains("services.ai.azure.com")
+            && api_version_supports_structured_output(&self.config.azure_api_version)
     }
 
-    async fn call_azure_openai(&self, request_body: Value) -> Result<Value> {
+    fn chat_completions_url(&self) -> String {
         // Determine endpoint type based on hostname
         let is_models_endpoint = self.config.azure_endpoint.contains("services.ai.azure.com");
 
-        let url = if is_models_endpoint {
+        if is_models_endpoint {
             // Models API endpoint format
             format!(
                 "{}/models/chat/completions?api-version={}",
@@ -66,7 +69,59 @@ impl AzureOpenAITranslationService {
                 self.config.azure_deployment_name,
                 self.config.azure_api_version
             )
+        }
+    }
+
+    /// The resource's deployments, which are what requests address as the model. Models API
+    /// endpoints list their models instead.
+    pub async fn list_deployments(&self) -> Result<Vec<ProviderModel>> {
+        let endpoint = self.config.azure_endpoint.trim().trim_end_matches('/');
+        let url = if endpoint.contains("services.ai.azure.com") {
+            format!(
+                "{}/models?api-version={}",
+                endpoint, self.config.azure_api_version
+            )
+        } else {
+            // Deployments are only listed by this older data-plane API version
+            format!("{}/openai/deployments?api-version=2022-12-01", endpoint)
         };
+        log::info!("Listing Azure OpenAI deployments from: {}", url);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("api-key", &self.config.azure_api_key)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!(
+                "Azure OpenAI deployment list request failed ({}): {}",
+                status,
+                error_text
+            ));
+        }
+
+        let body: Value = response.json().await?;
+        Ok(body["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|deployment| {
+                let name = deployment["id"].as_str()?.to_string();
+                let description = deployment["model"]
+                    .as_str()
+                    .filter(|model| *model != name)
+                    .map(|model| format!("Deployment of {}", model));
+                Some(ProviderModel { name, description })
+            })
+            .collect())
+    }
+
+    async fn call_azure_openai(&self, request_body: Value) -> Result<Value> {
+        let is_models_endpoint = self.config.azure_endpoint.contains("services.ai.azure.com");
+        let url = self.chat_completions_url();
 
         log::info!("Making Azure OpenAI request to: {}", url);
         log::info!(
@@ -82,14 +137,18 @@ impl AzureOpenAITranslationService {
             serde_json::to_string_pretty(&request_body).unwrap_or_default()
         );
 
-        let response = self
-            .client
-            .post(&url)
-            .header("api-key", &self.config.azure_api_key)
-            .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await?;
+        let response = send_with_retry(
+            RetryPolicy::from_config(&self.config),
+            "Azure OpenAI",
+            || {
+                self.client
+                    .post(&url)
+                    .header("api-key", &self.config.azure_api_key)
+                    .header("Content-Type", "application/json")
+                    .json(&request_body)
+            },
+        )
+        .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -99,211 +158,47 @@ impl AzureOpenAITranslationService {
                 status,
                 error_text
             );
-            return Err(anyhow::anyhow!(
-                "Azure OpenAI API request failed ({}): {}",
-                status,
-                error_text
-            ));
+            return Err(request_error(status, &error_text));
         }
 
         Ok(response.json().await?)
     }
 
-    fn parse_response_content(&self, content: &str) -> Result<TranslationResult> {
-        log::info!("API Response content: {}", content);
-
-        // Clean the content by removing control characters that can break JSON parsing
-        let cleaned_content = content
-            .chars()
-            .filter(|c| !c.is_control() || matches!(*c, '\n' | '\r' | '\t'))
-            .collect::<String>();
+    async fn call_azure_openai_stream(&self, mut request_body: Value) -> Result<reqwest::Response> {
+        let url = self.chat_completions_url();
+        request_body["stream"] = json!(true);
+
+        log::info!("Making streaming Azure OpenAI request to: {}", url);
+
+        let response = send_with_retry(
+            RetryPolicy::from_config(&self.config),
+            "Azure OpenAI",
+            || {
+                self.client
+                    .post(&url)
+                    .header("api-key", &self.config.azure_api_key)
+                    .header("Content-Type", "application/json")
+                    .json(&request_body)
+            },
+        )
+        .await?;
 
-        // Log if we removed any control characters
-        if cleaned_content != content {
-            log::warn!("Removed control characters from API response");
-            log::info!("Cleaned content: {}", cleaned_content);
-        }
-
-        // IMPORTANT: For reasoning models, the content is often just the JSON text itself
-        // First try direct JSON parsing
-        let parsed: Value = match serde_json::from_str(&cleaned_content) {
-            Ok(json) => {
-                log::info!("Successfully parsed JSON response directly");
-                json
-            }
-            Err(parse_error) => {
-                log::warn!("Failed to parse as JSON directly: {}", parse_error);
-
-                // Try to find and extract valid JSON from the response
-                if let Some(start_idx) = cleaned_content.find('{') {
-                    // Find the matching closing brace by counting braces
-                    let mut brace_count = 0;
-                    let mut end_idx = None;
-
-                    for (i, c) in cleaned_content[start_idx..].char_indices() {
-                        if c == '{' {
-                            brace_count += 1;
-                        } else if c == '}' {
-                            brace_count -= 1;
-                            if brace_count == 0 {
-                                end_idx = Some(start_idx + i + 1); // +1 to include the closing brace
-                                break;
-                            }
-                        }
-                    }
-
-                    if let Some(end_idx) = end_idx {
-                        let json_str = &cleaned_content[start_idx..end_idx];
-                        log::info!("Attempting to parse extracted JSON: {}", json_str);
-
-                        match serde_json::from_str::<Value>(json_str) {
-                            Ok(json) => {
-                                log::info!("Successfully parsed extracted JSON");
-                                json
-                            }
-                            Err(e) => {
-                                log::warn!("Failed to parse extracted JSON: {}", e);
-                                json!({
-                                    "detected_language": "unknown",
-                                    "translated_text": cleaned_content
-                                })
-                            }
-                        }
-                    } else {
-                        log::warn!("Could not find matching closing brace");
-                        json!({
-                            "detected_language": "unknown",
-                            "translated_text": cleaned_content
-                        })
-                    }
-                } else {
-                    log::warn!("No JSON structure found in response");
-                    json!({
-                        "detected_language": "unknown",
-                        "translated_text": cleaned_content
-                    })
-                }
-            }
-        };
-
-        // Extract detected language from parsed JSON with better error handling
-        let detected_language = if let Some(lang) = parsed["detected_language"].as_str() {
-            if !lang.is_empty() && lang.to_lowercase() != "unknown" {
-                log::info!(
-                    "✅ Successfully extracted detected_language from JSON: {}",
-                    lang
-                );
-                lang.to_string()
-            } else {
-                log::warn!(
-                    "⚠️ detected_language field found but is empty or 'unknown': {}",
-                    lang
-                );
-                "unknown".to_string()
-            }
-        } else {
-            log::error!(
-                "❌ No 'detected_language' field found in JSON. Available keys: {:?}",
-                parsed.as_object().map(|obj| obj.keys().collect::<Vec<_>>())
-            );
-            "unknown".to_string()
-        };
-        let translated_text = if let Some(text) = parsed["translated_text"].as_str() {
-            if !text.is_empty() {
-                log::info!(
-                    "✅ Successfully extracted translated_text from JSON (length: {})",
-                    text.len()
-                );
-                // Properly handle escaped newlines that Azure might return
-                text.replace("\\n", "\n")
-                    .replace("\\r\\n", "\n")
-                    .replace("\\r", "\n")
-                    .replace("\\t", "\t")
-            } else {
-                log::error!("❌ translated_text field found but is empty");
-                "translation failed".to_string()
-            }
-        } else {
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
             log::error!(
-                "❌ No 'translated_text' field found in JSON. Available keys: {:?}",
-                parsed.as_object().map(|obj| obj.keys().collect::<Vec<_>>())
-            );
-            // If translated_text field is missing, check if the whole response is just text
-            if parsed.is_string() {
-                let text = parsed.as_str().unwrap_or("translation failed");
-                log::info!(
-                    "📝 Using whole response as translated text (length: {})",
-                    text.len()
-                );
-                text.replace("\\n", "\n")
-                    .replace("\\r\\n", "\n")
-                    .replace("\\r", "\n")
-                    .replace("\\t", "\t")
-            } else {
-                "translation failed".to_string()
-            }
-        };
-
-        log::info!("Detected language: {}", detected_language);
-        log::info!("Target language: {}", self.config.target_language);
-        log::info!(
-            "Alternative target language: {}",
-            self.config.alternative_target_language
-        ); // Log if alternative language logic should have been applied
-        let detected_lower = detected_language.to_lowercase();
-        let target_lower = self.config.target_language.to_lowercase();
-
-        log::info!(
-            "Azure - Language analysis: detected='{}', target='{}', alternative='{}'",
-            detected_language,
-            self.config.target_language,
-            self.config.alternative_target_language
-        );
-
-        if detected_lower == target_lower {
-            log::info!(
-                "Azure - Alternative language logic SHOULD apply: detected '{}' matches target '{}', should translate to '{}'",
-                detected_language,
-                self.config.target_language,
-                self.config.alternative_target_language
-            );
-        } else {
-            log::info!(
-                "Azure - Primary target logic SHOULD apply: detected '{}' != target '{}', should translate to '{}'",
-                detected_language,
-                self.config.target_language,
-                self.config.target_language
+                "Azure OpenAI streaming request failed: Status: {}, Error: {}",
+                status,
+                error_text
             );
+            return Err(request_error(status, &error_text));
         }
-        log::info!(
-            "Translated text (first 100 chars): {}",
-            if translated_text.len() > 100 {
-                format!("{}...", &translated_text[..100])
-            } else {
-                translated_text.clone()
-            }
-        );
 
-        log::info!(
-            "Detected language (provider reported): {}",
-            detected_language
-        );
-        log::info!(
-            "Configured (effective) target language: {}",
-            self.config.target_language
-        );
-        Ok(TranslationResult {
-            detected_language,
-            translated_text,
-            target_language: self.config.target_language.clone(),
-        })
+        Ok(response)
     }
-}
 
-#[async_trait]
-impl TranslationProvider for AzureOpenAITranslationService {
-    async fn translate(&self, text: &str) -> Result<TranslationResult> {
-        let cleaned_text = clean_text_for_translation(text);
+    fn build_request_body(&self, request: &TranslationRequest) -> Result<Value> {
+        let cleaned_text = clean_text_for_translation(&request.text);
         log::info!("Cleaned text for translation: {}", cleaned_text);
 
         if self.config.model.trim().is_empty()
@@ -312,69 +207,40 @@ impl TranslationProvider for AzureOpenAITranslationService {
             log::error!(
                 "AzureOpenAITranslationService: model/deployment name is empty; cannot proceed"
             );
-            return Err(anyhow::anyhow!(
-                "Model / deployment not configured for Azure OpenAI provider"
+            return Err(ProviderError::not_configured(
+                "Model / deployment not configured for Azure OpenAI provider",
             ));
         }
 
-        // Check if this is an alternatives request (custom_prompt contains "alternatives")
-        let is_alternatives_request = self.config.custom_prompt.contains("alternatives");
+        let is_alternatives_request = request.is_alternatives();
 
         let (user_prompt, system_prompt) = if is_alternatives_request {
             // For alternatives requests, use the custom prompt directly as the system prompt
             log::info!("Using alternatives prompt");
             (
                 format!("\"{}\"", cleaned_text),
-                self.config.custom_prompt.clone(),
+                create_alternatives_prompt(request),
             )
         } else {
             // For regular translations, use the normal logic
             let user_prompt = format!(
                 "Text to translate into {}: \"{}\"",
-                self.config.target_language, cleaned_text
+                languages::english_name(&request.target_language),
+                cleaned_text
             );
-            let smart_prompt = create_smart_prompt(&self.config, None);
+            let smart_prompt = create_smart_prompt(&self.config, request);
             log::info!(
                 "Using smart prompt (pre-resolved target '{}')",
-                self.config.target_language
+                request.target_language
             );
             (user_prompt, smart_prompt)
         };
 
-        // Determine which token parameter to use based on model
-        let model_name = if !self.config.azure_deployment_name.is_empty() {
-            &self.config.azure_deployment_name
-        } else {
-            &self.config.model
-        };
-
-        // Check if this is a reasoning model
-        let is_reasoning_model = self.is_reasoning_model();
-
-        log::info!(
-            "Azure - Model name detected: {}, is_reasoning_model: {}",
-            model_name,
-            is_reasoning_model
-        );
-        log::info!(
-            "Azure - Deployment name: '{}', Model config: '{}'",
-            self.config.azure_deployment_name,
-            self.config.model
-        );
+        let model_name = self.model_name();
+        let settings = self.chat_settings();
+        let is_reasoning_model = settings.is_reasoning;
+        log::info!("Azure - Deployment {} settings: {:?}", model_name, settings);
 
-        // Role selection based on reasoning model type:
-        // - GPT-5 models: Use "system" role (they support system messages)
-        // - O-series models (o1, o3, o4-mini): Use "developer" role
-        // - Non-reasoning models: Use "system" role
-        let system_role = if is_reasoning_model
-            && (model_name.starts_with("o1")
-                || model_name.starts_with("o3")
-                || model_name.starts_with("o4-mini"))
-        {
-            "developer"
-        } else {
-            "system"
-        };
         let system_content = if is_alternatives_request {
             // For alternatives requests, use the system prompt directly with minimal formatting
             if is_reasoning_model {
@@ -384,19 +250,10 @@ impl TranslationProvider for AzureOpenAITranslationService {
             }
         } else if is_reasoning_model {
             // For reasoning models, use simplified instructions
-            if model_name.starts_with("gpt-5") {
-                // GPT-5 specific: even more simplified
-                format!(
-                    "{}\n\nPlease respond with valid JSON containing 'detected_language' and 'translated_text' fields.",
-                    system_prompt
-                )
-            } else {
-                // Other reasoning models
-                format!(
-                    "Please respond with valid JSON.\n\n{}\n\nRespond with JSON containing 'detected_language' and 'translated_text' fields.",
-                    system_prompt
-                )
-            }
+            format!(
+                "Please respond with valid JSON.\n\n{}\n\nRespond with JSON containing 'detected_language' and 'translated_text' fields.",
+                system_prompt
+            )
         } else {
             // For non-reasoning models, use detailed instructions
             format!(
@@ -408,7 +265,7 @@ impl TranslationProvider for AzureOpenAITranslationService {
         let mut request_body = json!({
             "messages": [
                 {
-                    "role": system_role,
+                    "role": settings.system_role,
                     "content": system_content
                 },
                 {
@@ -418,45 +275,39 @@ impl TranslationProvider for AzureOpenAITranslationService {
             ]
         });
 
-        // Use appropriate token parameter based on model type
+        // Reasoning models only accept `max_completion_tokens`, which also covers reasoning tokens
         if is_reasoning_model {
-            // GPT-5 models support "minimal" reasoning effort which is optimal
-            if model_name.starts_with("gpt-5") {
-                request_body["max_completion_tokens"] = json!(1000);
-                request_body["reasoning_effort"] = json!("minimal");
-                log::info!(
-                    "Azure - GPT-5 specific config: max_completion_tokens=1000, reasoning_effort=minimal"
-                );
-            } else if model_name.starts_with("gpt-4.1") {
-                // GPT-4.1 should use low reasoning effort
-                request_body["max_completion_tokens"] = json!(800);
-                request_body["reasoning_effort"] = json!("low");
-                log::info!(
-                    "Azure - GPT-4.1 specific config: max_completion_tokens=800, reasoning_effort=low"
-                );
-            } else {
-                // Other reasoning models (o1, o3, etc.) - use config or default to low
-                request_body["max_completion_tokens"] = json!(800);
-                let effort = self.config.reasoning_effort.as_deref().unwrap_or("low");
-                request_body["reasoning_effort"] = json!(effort);
-                log::info!("Azure - Using reasoning effort: {}", effort);
-            }
+            request_body["max_completion_tokens"] = json!(settings.max_output_tokens);
+        } else {
+            request_body["max_tokens"] = json!(settings.max_output_tokens);
+        }
+        if let Some(temperature) = settings.temperature {
+            request_body["temperature"] = json!(temperature);
+        }
+        if let Some(effort) = &settings.reasoning_effort {
+            request_body["reasoning_effort"] = json!(effort);
+        }
+        if let Some(verbosity) = &settings.verbosity {
+            request_body["verbosity"] = json!(verbosity);
+        }
 
-            // Add JSON mode for reasoning models to ensure proper JSON response
+        // For Azure Models API endpoints, we need to include the model parameter
+        // For Cognitive Services endpoints, the model is specified in the URL path
+        let is_models_endpoint = self.config.azure_endpoint.contains("services.ai.azure.com");
+
+        if self.supports_structured_output() {
+            // Structured outputs make the deployment follow the reply schema
+            request_body["response_format"] = json_schema_response_format(is_alternatives_request);
+            log::info!("Azure - Added JSON schema response format");
+        } else if is_reasoning_model {
+            // The Models API also serves non-OpenAI models, and older api-versions reject
+            // `json_schema`, so only request plain JSON mode there
             request_body["response_format"] = json!({
                 "type": "json_object"
             });
             log::info!("Azure - Added JSON mode for reasoning model");
-
-            // Note: Reasoning models don't support temperature, top_p, presence_penalty, frequency_penalty, logprobs, top_logprobs, logit_bias parameters
-        } else {
-            request_body["max_tokens"] = json!(800);
-            request_body["temperature"] = json!(0.3);
         }
 
-        // For Azure Models API endpoints, we need to include the model parameter
-        // For Cognitive Services endpoints, the model is specified in the URL path
-        let is_models_endpoint = self.config.azure_endpoint.contains("services.ai.azure.com");
         if is_models_endpoint && !self.config.azure_deployment_name.is_empty() {
             request_body["model"] = json!(self.config.azure_deployment_name);
             log::info!(
@@ -465,29 +316,38 @@ impl TranslationProvider for AzureOpenAITranslationService {
             );
         }
 
+        Ok(request_body)
+    }
+}
+
+#[async_trait]
+impl TranslationProvider for AzureOpenAITranslationService {
+    fn capabilities(&self) -> ProviderCapabilities {
+        // The chunk size follows the same token limit build_request_body() sends
+        let settings = self.chat_settings();
+        ProviderCapabilities {
+            alternatives: true,
+            phrase_alternatives: true,
+            streaming: true,
+            language_detection: true,
+            max_input_chars: None,
+            max_chunk_chars: Some(chunking::chars_for_output_tokens(settings.reply_tokens())),
+            transliteration: true,
+            // The Models API also serves non-OpenAI models, which only get plain JSON mode
+            structured_output: self.supports_structured_output(),
+            reasoning_effort: settings.is_reasoning,
+        }
+    }
+
+    async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResult> {
+        let request_body = self.build_request_body(request)?;
+
         let response = self.call_azure_openai(request_body).await?;
         log::info!(
             "Azure API Response: {}",
             serde_json::to_string_pretty(&response).unwrap_or_default()
         );
 
-        // Additional debugging for GPT-5 models
-        if model_name.starts_with("gpt-5") {
-            log::error!(
-                "GPT-5 DEBUG - Full response structure: {}",
-                serde_json::to_string_pretty(&response).unwrap_or_default()
-            );
-            if let Some(choices) = response["choices"].as_array() {
-                log::error!("GPT-5 DEBUG - Choices length: {}", choices.len());
-                if !choices.is_empty() {
-                    log::error!(
-                        "GPT-5 DEBUG - First choice: {}",
-                        serde_json::to_string_pretty(&choices[0]).unwrap_or_default()
-                    );
-                }
-            }
-        }
-
         // Better error handling for response structure
         let choices = response["choices"].as_array().ok_or_else(|| {
             anyhow::anyhow!(
@@ -503,6 +363,11 @@ impl TranslationProvider for AzureOpenAITranslationService {
             ));
         }
 
+        if choices[0]["finish_reason"].as_str() == Some("length") {
+            log::warn!("Azure OpenAI response was cut off at the output token limit");
+            return Err(truncated_reply_error());
+        }
+
         let message = &choices[0]["message"];
         if message.is_null() {
             return Err(anyhow::anyhow!(
@@ -520,22 +385,15 @@ impl TranslationProvider for AzureOpenAITranslationService {
 
         log::info!("Azure API Response Content: {}", content);
 
-        // Additional debugging for empty content
         if content.is_empty() {
-            log::error!(
-                "EMPTY CONTENT DEBUG - Model: {}, Content length: {}",
-                model_name,
-                content.len()
-            );
-            log::error!(
-                "EMPTY CONTENT DEBUG - Full message object: {}",
+            log::warn!(
+                "Azure OpenAI returned empty content from {}: {}",
+                self.model_name(),
                 serde_json::to_string_pretty(message).unwrap_or_default()
             );
         }
 
-        // Check if this is an alternatives request
-        let is_alternatives_request = self.config.custom_prompt.contains("alternatives");
-        if is_alternatives_request {
+        if request.is_alternatives() {
             // For alternatives requests, return the raw content as translated_text
             // The calling code will parse the JSON alternatives from it
             log::info!("Returning raw alternatives response");
@@ -543,23 +401,70 @@ impl TranslationProvider for AzureOpenAITranslationService {
                 detected_language: "unknown".to_string(),
                 translated_text: content.to_string(),
                 target_language: "alternatives".to_string(),
+                transliteration: None,
             })
         } else {
-            self.parse_response_content(content)
+            Ok(parse_translation_json(content, &request.target_language))
+        }
+    }
+
+    async fn translate_streaming(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: ChunkSink,
+    ) -> Result<TranslationResult> {
+        // Alternatives are parsed as a whole by the caller, so there is nothing to stream
+        if request.is_alternatives() {
+            return self.translate(request).await;
+        }
+
+        let request_body = self.build_request_body(request)?;
+
+        let response = self.call_azure_openai_stream(request_body).await?;
+        let content = read_chat_completion_stream(response, &on_chunk).await?;
+        if content.is_empty() {
+            return Err(anyhow::anyhow!("Empty streamed response from Azure OpenAI"));
         }
+
+        Ok(parse_translation_json(&content, &request.target_language))
+    }
+}
+
+/// Azure answers text its content filter refuses with a 400 and the `content_filter` code.
+fn request_error(status: reqwest::StatusCode, error_text: &str) -> anyhow::Error {
+    let message = format!(
+        "Azure OpenAI API request failed ({}): {}",
+        status, error_text
+    );
+    let code = serde_json::from_str::<Value>(error_text)
+        .ok()
+        .and_then(|body| body["error"]["code"].as_str().map(str::to_string));
+    if code.as_deref() == Some("content_filter") {
+        ProviderError::blocked(message)
+    } else {
+        ProviderError::status(status, message)
     }
 }
 
-fn is_reasoning_model_name(name: &str) -> bool {
-    let n = name.to_lowercase();
-    // Known Azure OpenAI reasoning model families and variants based on Microsoft documentation
-    // GPT-5 series: gpt-5, gpt-5-mini, gpt-5-nano, gpt-5-chat, gpt-5-thinking
-    n.starts_with("gpt-5") ||
-    n.starts_with("gpt5") ||
-    // O-series models: o1, o1-mini, o3, o3-mini, o3-pro, o4-mini
-    n.starts_with("o1") ||
-    n.starts_with("o3") ||
-    n.starts_with("o4-mini") ||
-    // Codex reasoning model
-    n.starts_with("codex-mini")
+// Api-versions are dated ("2024-10-21", "2025-01-01-preview"), so they compare as strings
+fn api_version_supports_structured_output(api_version: &str) -> bool {
+    let date = api_version.trim().get(..10).unwrap_or_default();
+    date.len() == 10 && date >= "2024-08-01"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structured_output_needs_a_recent_api_version() {
+        assert!(api_version_supports_structured_output("2024-08-01-preview"));
+        assert!(api_version_supports_structured_output("2024-10-21"));
+        assert!(api_version_supports_structured_output("2025-01-01-preview"));
+        assert!(!api_version_supports_structured_output("2024-06-01"));
+        assert!(!api_version_supports_structured_output(
+            "2024-05-01-preview"
+        ));
+        assert!(!api_version_supports_structured_output(""));
+    }
 }
@@ -1,9 +1,57 @@
 use crate::config::Config;
-use crate::translation::{TranslationProvider, TranslationResult, clean_text_for_translation};
+use crate::languages::{self, ProviderLanguage};
+use crate::retry::{RetryPolicy, send_with_retry};
+use crate::translation::{
+    ProviderCapabilities, ProviderError, TranslationProvider, TranslationRequest,
+    TranslationResult, clean_text_for_translation,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::{Value, json};
 
+// Per-request limits of the v3 `/translate` endpoint
+const MAX_TEXTS_PER_REQUEST: usize = 1000;
+const MAX_CHARS_PER_REQUEST: usize = 50_000;
+
+/// The translations of one input text.
+#[derive(Debug, Clone)]
+pub struct SegmentTranslation {
+    pub detected_language: Option<String>, // Language code, when the source was auto-detected
+    pub translations: Vec<(String, String)>, // (target language code, translated text)
+}
+
+impl SegmentTranslation {
+    /// The translation into `code`, matched case-insensitively ("zh-Hans" vs "zh-hans").
+    pub fn translation_to(&self, code: &str) -> Option<(&str, &str)> {
+        self.translations
+            .iter()
+            .find(|(to, _)| to.eq_ignore_ascii_case(code))
+            .map(|(to, text)| (to.as_str(), text.as_str()))
+    }
+}
+
+/// Splits `texts` into consecutive batches within the request limits. Every target language
+/// counts towards the character limit, so the budget shrinks with each extra target.
+fn batch_ranges(texts: &[&str], target_count: usize) -> Vec<std::ops::Range<usize>> {
+    let char_budget = MAX_CHARS_PER_REQUEST / target_count.max(1);
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut chars = 0;
+    for (index, text) in texts.iter().enumerate() {
+        let len = text.chars().count();
+        if index > start && (index - start >= MAX_TEXTS_PER_REQUEST || chars + len > char_budget) {
+            batches.push(start..index);
+            start = index;
+            chars = 0;
+        }
+        chars += len;
+    }
+    if start < texts.len() {
+        batches.push(start..texts.len());
+    }
+    batches
+}
+
 pub struct AzureTranslatorService {
     client: reqwest::Client,
     config: Config,
@@ -21,49 +69,48 @@ impl AzureTranslatorService {
         }
     }
 
-    async fn call_azure_translator(
-        &self,
-        text: &str,
-        to_language: &str,
-        from_language: Option<&str>,
-    ) -> Result<Value> {
+    /// POSTs `body` to a v3 endpoint such as `translate` or `dictionary/lookup`.
+    async fn post(&self, path: &str, query: &str, body: &Value) -> Result<Value> {
         let url = format!(
-            "{}/translate?api-version=3.0&to={}{}",
+            "{}/{}?api-version=3.0{}",
             self.config.azure_translator_endpoint.trim_end_matches('/'),
-            to_language,
-            from_language
-                .map(|lang| format!("&from={}", lang))
-                .unwrap_or_default()
+            path,
+            query
         );
 
-        let request_body = json!([{
-            "Text": text
-        }]);
-
         log::info!("Making Azure Translator request to: {}", url);
         log::info!(
             "Request body: {}",
-            serde_json::to_string_pretty(&request_body).unwrap_or_default()
+            serde_json::to_string_pretty(body).unwrap_or_default()
         );
 
-        let mut request = self
-            .client
-            .post(&url)
-            .header(
-                "Ocp-Apim-Subscription-Key",
-                &self.config.azure_translator_api_key,
-            )
-            .header("Content-Type", "application/json; charset=UTF-8");
+        let build_request = || {
+            let mut request = self
+                .client
+                .post(&url)
+                .header(
+                    "Ocp-Apim-Subscription-Key",
+                    &self.config.azure_translator_api_key,
+                )
+                .header("Content-Type", "application/json; charset=UTF-8");
+
+            // Add region header if specified (required for multi-service or regional resources)
+            if !self.config.azure_translator_region.is_empty() {
+                request = request.header(
+                    "Ocp-Apim-Subscription-Region",
+                    &self.config.azure_translator_region,
+                );
+            }
 
-        // Add region header if specified (required for multi-service or regional resources)
-        if !self.config.azure_translator_region.is_empty() {
-            request = request.header(
-                "Ocp-Apim-Subscription-Region",
-                &self.config.azure_translator_region,
-            );
-        }
+            request.json(body)
+        };
 
-        let response = request.json(&request_body).send().await?;
+        let response = send_with_retry(
+            RetryPolicy::from_config(&self.config),
+            "Azure Translator",
+            build_request,
+        )
+        .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -73,23 +120,72 @@ impl AzureTranslatorService {
                 status,
                 error_text
             );
-            return Err(anyhow::anyhow!(
-                "Azure Translator API request failed ({}): {}",
+            return Err(ProviderError::status(
                 status,
-                error_text
+                format!(
+                    "Azure Translator API request failed ({}): {}",
+                    status, error_text
+                ),
             ));
         }
 
         Ok(response.json().await?)
     }
 
-    fn parse_translator_response(&self, response: Value) -> Result<TranslationResult> {
+    async fn call_azure_translator(
+        &self,
+        texts: &[&str],
+        to_languages: &[&str],
+        from_language: Option<&str>,
+    ) -> Result<Value> {
+        let query = format!(
+            "{}{}",
+            to_languages
+                .iter()
+                .map(|lang| format!("&to={}", lang))
+                .collect::<String>(),
+            from_language
+                .map(|lang| format!("&from={}", lang))
+                .unwrap_or_default()
+        );
+        let request_body = Value::Array(texts.iter().map(|text| json!({ "Text": text })).collect());
+
+        self.post("translate", &query, &request_body).await
+    }
+
+    /// Translates several texts into every language in `to_languages`, batching them into as
+    /// few requests as the API limits allow. Results keep the order of `texts`.
+    pub async fn translate_segments(
+        &self,
+        texts: &[&str],
+        to_languages: &[&str],
+        from_language: Option<&str>,
+    ) -> Result<Vec<SegmentTranslation>> {
+        let mut segments = Vec::with_capacity(texts.len());
+        for batch in batch_ranges(texts, to_languages.len()) {
+            let response = self
+                .call_azure_translator(&texts[batch.clone()], to_languages, from_language)
+                .await?;
+            let parsed = Self::parse_translator_response(&response)?;
+            if parsed.len() != batch.len() {
+                return Err(anyhow::anyhow!(
+                    "Azure Translator returned {} results for {} texts",
+                    parsed.len(),
+                    batch.len()
+                ));
+            }
+            segments.extend(parsed);
+        }
+        Ok(segments)
+    }
+
+    fn parse_translator_response(response: &Value) -> Result<Vec<SegmentTranslation>> {
         log::info!(
             "Azure Translator Response: {}",
-            serde_json::to_string_pretty(&response).unwrap_or_default()
+            serde_json::to_string_pretty(response).unwrap_or_default()
         );
 
-        // Azure Translator returns an array with translation results
+        // Azure Translator returns one result per input text
         let results = response
             .as_array()
             .ok_or_else(|| anyhow::anyhow!("Invalid response format: expected array"))?;
@@ -98,213 +194,362 @@ impl AzureTranslatorService {
             return Err(anyhow::anyhow!("Empty response from Azure Translator"));
         }
 
-        let result = &results[0];
+        results
+            .iter()
+            .map(|result| {
+                // Only present when the source language was not given
+                let detected_language = result["detectedLanguage"]["language"]
+                    .as_str()
+                    .map(str::to_string);
+
+                // One translation per requested target language
+                let translations = result
+                    .get("translations")
+                    .and_then(|v| v.as_array())
+                    .ok_or_else(|| anyhow::anyhow!("No translations found in response"))?
+                    .iter()
+                    .map(|translation| {
+                        let to = translation["to"].as_str().unwrap_or("unknown").to_string();
+                        let text = translation["text"]
+                            .as_str()
+                            .ok_or_else(|| anyhow::anyhow!("No translation text found"))?
+                            .to_string();
+                        Ok((to, text))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                if translations.is_empty() {
+                    return Err(anyhow::anyhow!("Empty translations array"));
+                }
+
+                Ok(SegmentTranslation {
+                    detected_language,
+                    translations,
+                })
+            })
+            .collect()
+    }
 
-        // Extract detected language information
-        let detected_language = if let Some(detected) = result.get("detectedLanguage") {
-            detected
-                .get("language")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown")
-                .to_string()
-        } else {
-            "unknown".to_string()
-        };
+    /// The dictionary only pairs English with other languages, so synonyms are found by looking
+    /// the text up in English (or, for English text, in another configured language) and
+    /// collecting the back-translations.
+    fn dictionary_pivot_language(&self, language_code: &str) -> String {
+        if !language_code.eq_ignore_ascii_case("en") {
+            return "en".to_string();
+        }
+        [
+            &self.config.target_language,
+            &self.config.alternative_target_language,
+        ]
+        .into_iter()
+        .map(|tag| self.map_language_tag_to_code(tag))
+        .find(|code| !code.eq_ignore_ascii_case("en"))
+        .unwrap_or_else(|| "es".to_string())
+    }
 
-        // Extract translation text
-        let translations = result
-            .get("translations")
-            .and_then(|v| v.as_array())
-            .ok_or_else(|| anyhow::anyhow!("No translations found in response"))?;
+    /// Alternatives from `/dictionary/lookup`, with a usage example per alternative from
+    /// `/dictionary/examples`. Returns the `{"alternatives": [...], "details": [...]}` JSON
+    /// the alternatives parser expects.
+    async fn dictionary_alternatives(&self, request: &TranslationRequest) -> Result<Value> {
+        let text = request.text.trim();
+        let from = self.map_language_tag_to_code(&request.target_language);
+        let to = self.dictionary_pivot_language(&from);
+        let query = format!("&from={}&to={}", from, to);
+
+        let lookup = self
+            .post("dictionary/lookup", &query, &json!([{ "Text": text }]))
+            .await?;
 
-        if translations.is_empty() {
-            return Err(anyhow::anyhow!("Empty translations array"));
+        // (alternative, part of speech, the pivot translation it came from)
+        let mut candidates: Vec<(String, String, String)> = Vec::new();
+        for translation in lookup[0]["translations"].as_array().into_iter().flatten() {
+            let part_of_speech = translation["posTag"].as_str().unwrap_or_default();
+            let pivot = translation["normalizedTarget"].as_str().unwrap_or_default();
+            for back in translation["backTranslations"]
+                .as_array()
+                .into_iter()
+                .flatten()
+            {
+                let Some(alternative) = back["displayText"].as_str() else {
+                    continue;
+                };
+                if alternative.eq_ignore_ascii_case(text)
+                    || candidates
+                        .iter()
+                        .any(|(existing, _, _)| existing.eq_ignore_ascii_case(alternative))
+                {
+                    continue;
+                }
+                candidates.push((
+                    alternative.to_string(),
+                    part_of_speech.to_string(),
+                    pivot.to_string(),
+                ));
+            }
         }
-
-        let translation = &translations[0];
-        let translated_text = translation
-            .get("text")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("No translation text found"))?
-            .to_string();
-
-        let target_lang = translation
-            .get("to")
-            .and_then(|v| v.as_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        log::info!("Detected language: {}", detected_language);
-        log::info!("Target language: {}", target_lang);
+        // Translations come sorted by confidence, so the first few are the most relevant
+        candidates.truncate(5);
         log::info!(
-            "Alternative target language: {}",
-            self.config.alternative_target_language
+            "Dictionary lookup found {} alternatives for '{}'",
+            candidates.len(),
+            text
         );
 
-        // The actual target language is determined by what we requested from the API
-        // and should match the target_lang returned in the response
-        let actual_target_language = self.map_language_code_to_name(&target_lang);
-
-        log::info!(
-            "Translated text (first 100 chars): {}",
-            if translated_text.len() > 100 {
-                format!("{}...", &translated_text[..100])
-            } else {
-                translated_text.clone()
+        let examples = if candidates.is_empty() {
+            Vec::new()
+        } else {
+            let body = Value::Array(
+                candidates
+                    .iter()
+                    .map(|(alternative, _, pivot)| {
+                        json!({ "Text": alternative.to_lowercase(), "Translation": pivot })
+                    })
+                    .collect(),
+            );
+            // Examples are a nice-to-have; the alternatives are still useful without them
+            match self.post("dictionary/examples", &query, &body).await {
+                Ok(response) => response.as_array().cloned().unwrap_or_default(),
+                Err(e) => {
+                    log::warn!("Failed to load dictionary examples: {}", e);
+                    Vec::new()
+                }
             }
-        );
+        };
 
-        Ok(TranslationResult {
-            detected_language: self.map_language_code_to_name(&detected_language),
-            translated_text,
-            target_language: actual_target_language,
-        })
+        let details: Vec<Value> = candidates
+            .iter()
+            .enumerate()
+            .map(|(index, (alternative, part_of_speech, _))| {
+                let example = examples
+                    .get(index)
+                    .and_then(|entry| entry["examples"].as_array())
+                    .and_then(|examples| examples.first())
+                    .map(|example| {
+                        format!(
+                            "{}{}{}",
+                            example["sourcePrefix"].as_str().unwrap_or_default(),
+                            example["sourceTerm"].as_str().unwrap_or_default(),
+                            example["sourceSuffix"].as_str().unwrap_or_default()
+                        )
+                    });
+                json!({
+                    "text": alternative,
+                    "part_of_speech": Some(part_of_speech).filter(|pos| !pos.is_empty()),
+                    "example": example,
+                })
+            })
+            .collect();
+
+        Ok(json!({
+            "alternatives": candidates
+                .iter()
+                .map(|(alternative, _, _)| alternative)
+                .collect::<Vec<_>>(),
+            "details": details,
+        }))
     }
 
-    fn map_language_code_to_name(&self, code: &str) -> String {
-        // Map common language codes to full names for consistency
-        match code.to_lowercase().as_str() {
-            "en" => "English".to_string(),
-            "es" => "Spanish".to_string(),
-            "fr" => "French".to_string(),
-            "de" => "German".to_string(),
-            "it" => "Italian".to_string(),
-            "pt" => "Portuguese".to_string(),
-            "ru" => "Russian".to_string(),
-            "ja" => "Japanese".to_string(),
-            "ko" => "Korean".to_string(),
-            "zh" | "zh-hans" => "Chinese".to_string(),
-            "zh-hant" => "Chinese (Traditional)".to_string(),
-            "ar" => "Arabic".to_string(),
-            "hi" => "Hindi".to_string(),
-            "nl" => "Dutch".to_string(),
-            "sv" => "Swedish".to_string(),
-            "no" | "nb" => "Norwegian".to_string(),
-            "da" => "Danish".to_string(),
-            "fi" => "Finnish".to_string(),
-            "pl" => "Polish".to_string(),
-            "tr" => "Turkish".to_string(),
-            "cs" => "Czech".to_string(),
-            "hu" => "Hungarian".to_string(),
-            _ => code.to_string(), // Return the code as-is if not mapped
-        }
+    /// Romanizes `text`, which is in the language `language_code`. Returns None for languages
+    /// that are already written in Latin script.
+    async fn transliterate(&self, text: &str, language_code: &str) -> Result<Option<String>> {
+        let Some(script) = languages::script(language_code).filter(|script| script != "Latn")
+        else {
+            return Ok(None);
+        };
+        let query = format!(
+            "&language={}&fromScript={}&toScript=Latn",
+            language_code, script
+        );
+        let response = self
+            .post("transliterate", &query, &json!([{ "Text": text }]))
+            .await?;
+        Ok(response[0]["text"].as_str().map(str::to_string))
     }
 
-    fn map_language_name_to_code(&self, name: &str) -> String {
-        // Map language names to Azure Translator language codes
-        match name.to_lowercase().as_str() {
-            "english" => "en".to_string(),
-            "spanish" => "es".to_string(),
-            "french" => "fr".to_string(),
-            "german" => "de".to_string(),
-            "italian" => "it".to_string(),
-            "portuguese" => "pt".to_string(),
-            "russian" => "ru".to_string(),
-            "japanese" => "ja".to_string(),
-            "korean" => "ko".to_string(),
-            "chinese" | "chinese (simplified)" => "zh-Hans".to_string(),
-            "chinese (traditional)" => "zh-Hant".to_string(),
-            "arabic" => "ar".to_string(),
-            "hindi" => "hi".to_string(),
-            "dutch" => "nl".to_string(),
-            "swedish" => "sv".to_string(),
-            "norwegian" => "nb".to_string(),
-            "danish" => "da".to_string(),
-            "finnish" => "fi".to_string(),
-            "polish" => "pl".to_string(),
-            "turkish" => "tr".to_string(),
-            "czech" => "cs".to_string(),
-            "hungarian" => "hu".to_string(),
-            _ => name.to_string(), // Return the name as-is if not mapped
+    /// Languages Azure Translator can translate between. The endpoint needs no key.
+    pub async fn supported_languages(&self) -> Result<Vec<ProviderLanguage>> {
+        let url = format!(
+            "{}/languages?api-version=3.0&scope=translation",
+            self.config.azure_translator_endpoint.trim_end_matches('/')
+        );
+        let response = self
+            .client
+            .get(&url)
+            .header("Accept-Language", "en") // English names, native ones come separately
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Azure Translator languages request failed ({})",
+                response.status()
+            ));
         }
+
+        let body: Value = response.json().await?;
+        let languages = body["translation"]
+            .as_object()
+            .ok_or_else(|| anyhow::anyhow!("Invalid languages response: no translation scope"))?;
+        Ok(languages
+            .iter()
+            .map(|(code, language)| ProviderLanguage {
+                code: code.clone(),
+                name: language["name"].as_str().unwrap_or(code).to_string(),
+                native_name: language["nativeName"].as_str().map(str::to_string),
+            })
+            .collect())
+    }
+
+    fn map_language_code_to_tag(&self, code: &str) -> String {
+        languages::tag(code)
+    }
+
+    fn map_language_tag_to_code(&self, tag: &str) -> String {
+        languages::provider_code("azure_translator", tag).unwrap_or_else(|| tag.to_string())
     }
 }
 
 #[async_trait]
 impl TranslationProvider for AzureTranslatorService {
-    async fn translate(&self, text: &str) -> Result<TranslationResult> {
-        let cleaned_text = clean_text_for_translation(text);
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            alternatives: true, // Dictionary lookup, for single words and a few idioms
+            phrase_alternatives: false,
+            streaming: false,
+            language_detection: true,
+            // Both candidate targets count towards the 50,000 character request limit
+            max_input_chars: Some(MAX_CHARS_PER_REQUEST / 2),
+            max_chunk_chars: None,
+            transliteration: true,
+            structured_output: false,
+            reasoning_effort: false,
+        }
+    }
+
+    async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResult> {
+        if request.is_alternatives() {
+            let alternatives = self.dictionary_alternatives(request).await?;
+            return Ok(TranslationResult {
+                detected_language: request.target_language.clone(),
+                translated_text: alternatives.to_string(),
+                target_language: "alternatives".to_string(),
+                transliteration: None,
+            });
+        }
+
+        let cleaned_text = clean_text_for_translation(&request.text);
         log::info!("Cleaned text for translation: {}", cleaned_text);
 
         // Check if user specified a source language override
-        let source_language_code = self
-            .config
-            .user_source_language
+        let source_language_code = request
+            .source_language
             .as_ref()
-            .map(|lang| self.map_language_name_to_code(lang));
-
-        // First, detect the language if not specified by user
-        let detected_language = if source_language_code.is_some() {
-            source_language_code.clone().unwrap()
-        } else {
-            // Make a detection-only request to Azure Translator
-            let detect_response = self
-                .call_azure_translator(&cleaned_text, "en", None) // Use 'en' as dummy target for detection
-                .await?;
-
-            let results = detect_response
-                .as_array()
-                .ok_or_else(|| anyhow::anyhow!("Invalid response format for detection"))?;
-
-            if results.is_empty() {
-                return Err(anyhow::anyhow!(
-                    "Empty response from Azure Translator for detection"
-                ));
+            .map(|lang| self.map_language_tag_to_code(lang));
+
+        let primary_target_code = self.map_language_tag_to_code(&request.target_language);
+        let alternative_target_code =
+            self.map_language_tag_to_code(&request.alternative_target_language);
+
+        // Without a source override the target depends on the detected language, so translate
+        // into both candidates in one call (one round trip instead of detect-then-translate)
+        // and pick the right one locally. Characters are billed per target language, which is
+        // what the former detection request cost as well.
+        let source_is_primary_target = request
+            .source_language
+            .as_deref()
+            .is_some_and(|source| languages::same_base_language(source, &request.target_language));
+        let to_languages: Vec<&str> = match &source_language_code {
+            Some(_) if source_is_primary_target => {
+                vec![&alternative_target_code]
             }
-
-            let result = &results[0];
-            if let Some(detected) = result.get("detectedLanguage") {
-                detected
-                    .get("language")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("en")
-                    .to_string()
-            } else {
-                "en".to_string()
-            }
-        };
-
-        log::info!("Detected language code: {}", detected_language);
-
-        // Determine the actual target language based on smart switching logic
-        let target_language_code = {
-            let detected_lower = detected_language.to_lowercase();
-            let primary_target_code = self.map_language_name_to_code(&self.config.target_language);
-            let primary_target_lower = primary_target_code.to_lowercase();
-
-            if detected_lower == primary_target_lower {
-                log::info!(
-                    "Smart switching: detected '{}' matches primary target '{}', using alternative target '{}'",
-                    detected_language,
-                    primary_target_code,
-                    self.config.alternative_target_language
-                );
-                self.map_language_name_to_code(&self.config.alternative_target_language)
-            } else {
-                log::info!(
-                    "Normal translation: detected '{}' != primary target '{}', using primary target",
-                    detected_language,
-                    primary_target_code
-                );
-                primary_target_code
+            Some(_) => vec![&primary_target_code],
+            None if primary_target_code.eq_ignore_ascii_case(&alternative_target_code) => {
+                vec![&primary_target_code]
             }
+            None => vec![&primary_target_code, &alternative_target_code],
         };
-
-        log::info!("Final target language code: {}", target_language_code);
         if let Some(ref source_code) = source_language_code {
             log::info!("Source language code (user specified): {}", source_code);
         } else {
             log::info!("Source language: auto-detect");
         }
 
-        let response = self
-            .call_azure_translator(
-                &cleaned_text,
-                &target_language_code,
+        let segment = self
+            .translate_segments(
+                &[cleaned_text.as_str()],
+                &to_languages,
                 source_language_code.as_deref(),
             )
-            .await?;
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Empty response from Azure Translator"))?;
+
+        let detected_language = source_language_code
+            .or(segment.detected_language.clone())
+            .unwrap_or_else(|| "unknown".to_string());
+        log::info!("Detected language code: {}", detected_language);
+
+        // Smart switching: text already in the primary target goes to the alternative target.
+        // Variants count as a match, so Azure's "pt" matches a "pt-BR" target.
+        let (target_language, target_language_code) = if languages::same_base_language(
+            &detected_language,
+            &request.target_language,
+        ) {
+            log::info!(
+                "Smart switching: detected '{}' matches primary target '{}', using alternative target '{}'",
+                detected_language,
+                primary_target_code,
+                alternative_target_code
+            );
+            (
+                request.alternative_target_language.clone(),
+                &alternative_target_code,
+            )
+        } else {
+            (request.target_language.clone(), &primary_target_code)
+        };
+
+        let (target_language_code, translated_text) = segment
+            .translation_to(target_language_code)
+            .ok_or_else(|| {
+                anyhow::anyhow!("No translation for '{}' in response", target_language_code)
+            })?;
+
+        log::info!("Final target language code: {}", target_language_code);
+
+        // The romanization is an extra; the translation is still returned if it fails
+        let transliteration = if request.wants_transliteration(&target_language) {
+            self.transliterate(translated_text, target_language_code)
+                .await
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to transliterate the translation: {}", e);
+                    None
+                })
+        } else {
+            None
+        };
+
+        Ok(TranslationResult {
+            detected_language: self.map_language_code_to_tag(&detected_language),
+            translated_text: translated_text.to_string(),
+            target_language,
+            transliteration,
+        })
+    }
+}
 
-        self.parse_translator_response(response)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_ranges() {
+        let long = "a".repeat(30_000);
+        assert_eq!(batch_ranges(&["a", "b"], 1), vec![0..2]);
+        assert_eq!(batch_ranges(&[&long, &long], 1), vec![0..1, 1..2]);
+        // A text above the budget still gets its own request
+        assert_eq!(batch_ranges(&[&long], 2), vec![0..1]);
+        assert_eq!(batch_ranges(&["a"; 1500], 1), vec![0..1000, 1000..1500]);
+        assert!(batch_ranges(&[], 1).is_empty());
     }
 }
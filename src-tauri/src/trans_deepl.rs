@@ -0,0 +1,305 @@
+use crate::config::Config;
+use crate::languages::{self, ProviderLanguage};
+use crate::retry::{RetryPolicy, send_with_retry};
+use crate::translation::{
+    ProviderCapabilities, ProviderError, TranslationProvider, TranslationRequest,
+    TranslationResult, clean_text_for_translation,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+const DEEPL_FREE_URL: &str = "https://api-free.deepl.com";
+const DEEPL_PRO_URL: &str = "https://api.deepl.com";
+
+/// Returns the API host for a key. DeepL API Free keys always end in ":fx".
+pub fn deepl_base_url(api_key: &str) -> &'static str {
+    if api_key.trim().ends_with(":fx") {
+        DEEPL_FREE_URL
+    } else {
+        DEEPL_PRO_URL
+    }
+}
+
+pub struct DeepLTranslationService {
+    client: reqwest::Client,
+    config: Config,
+}
+
+impl DeepLTranslationService {
+    pub fn new(config: Config) -> Self {
+        log::info!(
+            "Creating DeepLTranslationService with endpoint: {}",
+            deepl_base_url(&config.deepl_api_key)
+        );
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    async fn call_deepl(
+        &self,
+        text: &str,
+        target_code: &str,
+        source_code: Option<&str>,
+        glossary_id: Option<&str>,
+    ) -> Result<Value> {
+        let url = format!(
+            "{}/v2/translate",
+            deepl_base_url(&self.config.deepl_api_key)
+        );
+
+        let mut request_body = json!({
+            "text": [text],
+            "target_lang": target_code,
+            "preserve_formatting": true
+        });
+
+        if let Some(source_code) = source_code {
+            request_body["source_lang"] = json!(source_code);
+        }
+        if let Some(glossary_id) = glossary_id {
+            request_body["glossary_id"] = json!(glossary_id);
+        }
+
+        // "prefer_*" values fall back to the default for target languages without formality support
+        let formality = self.config.deepl_formality.trim();
+        if !formality.is_empty() && formality != "default" {
+            request_body["formality"] = json!(formality);
+        }
+
+        log::info!("Making DeepL request to: {}", url);
+        log::info!(
+            "Request body: {}",
+            serde_json::to_string_pretty(&request_body).unwrap_or_default()
+        );
+
+        let response = send_with_retry(RetryPolicy::from_config(&self.config), "DeepL", || {
+            self.client
+                .post(&url)
+                .header(
+                    "Authorization",
+                    format!("DeepL-Auth-Key {}", self.config.deepl_api_key.trim()),
+                )
+                .header("Content-Type", "application/json")
+                .json(&request_body)
+        })
+        .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            log::error!(
+                "DeepL API request failed: Status: {}, Error: {}",
+                status,
+                error_text
+            );
+            return Err(ProviderError::status(
+                status,
+                format!("DeepL API request failed ({}): {}", status, error_text),
+            ));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Returns the detected source language code and the translated text.
+    fn parse_deepl_response(&self, response: &Value) -> Result<(String, String)> {
+        log::info!(
+            "DeepL Response: {}",
+            serde_json::to_string_pretty(response).unwrap_or_default()
+        );
+
+        let translation = response
+            .get("translations")
+            .and_then(|v| v.as_array())
+            .and_then(|translations| translations.first())
+            .ok_or_else(|| anyhow::anyhow!("No translations found in DeepL response"))?;
+
+        let detected_code = translation
+            .get("detected_source_language")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let translated_text = translation
+            .get("text")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("No translation text found"))?
+            .to_string();
+
+        Ok((detected_code, translated_text))
+    }
+
+    /// DeepL's languages: the source languages, which use the plain language codes ("EN"),
+    /// followed by the target-only regional and script variants ("EN-GB", "PT-BR", "ZH-HANT").
+    /// The plain codes come first, so they stay the code of their base language.
+    pub async fn supported_languages(&self) -> Result<Vec<ProviderLanguage>> {
+        let mut languages = self.languages("source").await?;
+        for target in self.languages("target").await? {
+            if !languages
+                .iter()
+                .any(|language| language.code.eq_ignore_ascii_case(&target.code))
+            {
+                languages.push(target);
+            }
+        }
+        Ok(languages)
+    }
+
+    /// One of DeepL's language lists; `kind` is "source" or "target".
+    async fn languages(&self, kind: &str) -> Result<Vec<ProviderLanguage>> {
+        let url = format!(
+            "{}/v2/languages?type={}",
+            deepl_base_url(&self.config.deepl_api_key),
+            kind
+        );
+        let response = self
+            .client
+            .get(&url)
+            .header(
+                "Authorization",
+                format!("DeepL-Auth-Key {}", self.config.deepl_api_key.trim()),
+            )
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "DeepL {} languages request failed ({})",
+                kind,
+                response.status()
+            ));
+        }
+
+        let languages: Vec<Value> = response.json().await?;
+        Ok(languages
+            .iter()
+            .filter_map(|language| {
+                Some(ProviderLanguage {
+                    code: language["language"].as_str()?.to_string(),
+                    name: language["name"].as_str()?.to_string(),
+                    native_name: None,
+                })
+            })
+            .collect())
+    }
+
+    fn map_language_code_to_tag(&self, code: &str) -> String {
+        // DeepL reports upper-case codes, with regional variants for some targets
+        languages::tag(code)
+    }
+
+    fn map_language_tag_to_code(&self, tag: &str) -> String {
+        let code = languages::provider_code("deepl", tag)
+            .unwrap_or_else(|| tag.to_string())
+            .to_uppercase();
+        // These targets need a regional variant; the plain codes are deprecated
+        match code.as_str() {
+            "EN" => "EN-US".to_string(),
+            "PT" => "PT-PT".to_string(),
+            "ZH" => "ZH-HANS".to_string(),
+            _ => code,
+        }
+    }
+
+    /// Source languages are never regional in DeepL ("EN", not "EN-US").
+    fn source_code(target_code: &str) -> String {
+        target_code
+            .split('-')
+            .next()
+            .unwrap_or(target_code)
+            .to_uppercase()
+    }
+}
+
+#[async_trait]
+impl TranslationProvider for DeepLTranslationService {
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            alternatives: false,
+            phrase_alternatives: false,
+            streaming: false,
+            language_detection: true,
+            max_input_chars: Some(128 * 1024), // Request bodies are capped at 128 KiB
+            max_chunk_chars: None,
+            transliteration: false,
+            structured_output: false,
+            reasoning_effort: false,
+        }
+    }
+
+    async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResult> {
+        if request.is_alternatives() {
+            return Err(anyhow::anyhow!("DeepL cannot generate alternatives"));
+        }
+
+        let cleaned_text = clean_text_for_translation(&request.text);
+        log::info!("Cleaned text for translation: {}", cleaned_text);
+
+        // Check if user specified a source language override
+        let source_language_code = request
+            .source_language
+            .as_ref()
+            .map(|lang| Self::source_code(&self.map_language_tag_to_code(lang)));
+
+        let primary_target_code = self.map_language_tag_to_code(&request.target_language);
+
+        // Glossaries are bound to a source/target pair, so DeepL only accepts them
+        // together with an explicit source language
+        let glossary_id = self
+            .config
+            .deepl_glossary_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|id| !id.is_empty());
+        if glossary_id.is_some() && source_language_code.is_none() {
+            log::info!("Skipping DeepL glossary: it requires a source language override");
+        }
+        let glossary_id = glossary_id.filter(|_| source_language_code.is_some());
+
+        // DeepL has no detection endpoint, so translate into the primary target and
+        // only make a second call when the text was already in that language
+        let response = self
+            .call_deepl(
+                &cleaned_text,
+                &primary_target_code,
+                source_language_code.as_deref(),
+                glossary_id,
+            )
+            .await?;
+        let (detected_code, mut translated_text) = self.parse_deepl_response(&response)?;
+        log::info!("Detected language code: {}", detected_code);
+
+        // Variants of the primary target ("EN" for "en-GB") count as a match
+        let mut target_code = primary_target_code;
+        let mut target_language = &request.target_language;
+        if languages::same_base_language(&detected_code, target_language) {
+            let alternative_code =
+                self.map_language_tag_to_code(&request.alternative_target_language);
+            log::info!(
+                "Smart switching: detected '{}' matches primary target '{}', using alternative target '{}'",
+                detected_code,
+                target_code,
+                alternative_code
+            );
+
+            // The glossary was made for the primary target, so it cannot be reused here
+            let response = self
+                .call_deepl(&cleaned_text, &alternative_code, Some(&detected_code), None)
+                .await?;
+            translated_text = self.parse_deepl_response(&response)?.1;
+            target_code = alternative_code;
+            target_language = &request.alternative_target_language;
+        }
+
+        log::info!("Final target language code: {}", target_code);
+
+        Ok(TranslationResult {
+            detected_language: self.map_language_code_to_tag(&detected_code),
+            translated_text,
+            target_language: target_language.clone(),
+            transliteration: None,
+        })
+    }
+}
@@ -0,0 +1,360 @@
+use crate::chunking;
+use crate::config::Config;
+use crate::languages;
+use crate::models::{self, ChatModelSettings};
+use crate::retry::{RetryPolicy, send_with_retry};
+use crate::streaming::read_sse_stream;
+use crate::translation::{
+    ChunkSink, ProviderCapabilities, ProviderError, TranslationProvider, TranslationRequest,
+    TranslationResult, clean_text_for_translation, create_alternatives_prompt, create_smart_prompt,
+    parse_translation_json, truncated_reply_error,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+pub struct GeminiTranslationService {
+    client: reqwest::Client,
+    config: Config,
+}
+
+impl GeminiTranslationService {
+    pub fn new(config: Config) -> Self {
+        log::info!(
+            "Creating GeminiTranslationService with model: {}",
+            config.model
+        );
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    fn model_url(&self, method: &str) -> String {
+        // Accept both "gemini-2.5-flash" and the "models/gemini-2.5-flash" form returned by the API
+        let model = self.config.model.trim().trim_start_matches("models/");
+        format!("{}/models/{}:{}", GEMINI_BASE_URL, model, method)
+    }
+
+    fn build_request_body(&self, request: &TranslationRequest) -> Result<Value> {
+        let cleaned_text = clean_text_for_translation(&request.text);
+        log::info!("Cleaned text for translation: {}", cleaned_text);
+
+        if self.config.model.trim().is_empty() {
+            log::error!("GeminiTranslationService: model is empty; cannot proceed");
+            return Err(ProviderError::not_configured(
+                "Model not configured for Gemini provider",
+            ));
+        }
+
+        let is_alternatives_request = request.is_alternatives();
+
+        let (user_prompt, system_prompt) = if is_alternatives_request {
+            log::info!("Using alternatives prompt");
+            (
+                format!("\"{}\"", cleaned_text),
+                create_alternatives_prompt(request),
+            )
+        } else {
+            let user_prompt = format!(
+                "Text to translate into {}: \"{}\"",
+                languages::english_name(&request.target_language),
+                cleaned_text
+            );
+            let smart_prompt = create_smart_prompt(&self.config, request);
+            let system_prompt = format!(
+                "{}\n\nRespond with JSON containing 'detected_language' and 'translated_text' fields. Preserve line breaks and paragraph structure in the translation.",
+                smart_prompt
+            );
+            (user_prompt, system_prompt)
+        };
+
+        // JSON mode is enabled for both translations and alternatives, which are JSON as well
+        let settings = self.chat_settings();
+        let mut request_body = json!({
+            "systemInstruction": {
+                "parts": [{ "text": system_prompt }]
+            },
+            "contents": [
+                {
+                    "role": "user",
+                    "parts": [{ "text": user_prompt }]
+                }
+            ],
+            "generationConfig": {
+                "responseMimeType": "application/json",
+                "maxOutputTokens": settings.max_output_tokens
+            }
+        });
+        if let Some(temperature) = settings.temperature {
+            request_body["generationConfig"]["temperature"] = json!(temperature);
+        }
+        Ok(request_body)
+    }
+
+    fn chat_settings(&self) -> ChatModelSettings {
+        let mut settings = models::chat_settings(&self.config, "gemini", &self.config.model);
+        // Gemini 2.5 and later think by default, and thinking tokens count against
+        // maxOutputTokens, so less of the limit is left for the reply
+        let is_reasoning_set = self
+            .config
+            .model_config("gemini", &self.config.model)
+            .and_then(|m| m.is_reasoning)
+            .is_some();
+        if !is_reasoning_set && is_thinking_model(&self.config.model) {
+            settings.is_reasoning = true;
+        }
+        settings
+    }
+
+    async fn call_gemini(&self, request_body: Value) -> Result<Value> {
+        let url = self.model_url("generateContent");
+
+        log::info!("Making Gemini request to: {}", url);
+        log::info!(
+            "Request body: {}",
+            serde_json::to_string_pretty(&request_body).unwrap_or_default()
+        );
+
+        let response = send_with_retry(RetryPolicy::from_config(&self.config), "Gemini", || {
+            self.client
+                .post(&url)
+                .header("x-goog-api-key", &self.config.gemini_api_key)
+                .header("Content-Type", "application/json")
+                .json(&request_body)
+        })
+        .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            log::error!(
+                "Gemini API request failed: Status: {}, Error: {}",
+                status,
+                error_text
+            );
+            return Err(ProviderError::status(
+                status,
+                format!("Gemini API request failed ({}): {}", status, error_text),
+            ));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    async fn call_gemini_stream(&self, request_body: Value) -> Result<reqwest::Response> {
+        // `alt=sse` switches the streaming endpoint from a JSON array to server-sent events
+        let url = format!("{}?alt=sse", self.model_url("streamGenerateContent"));
+        log::info!("Making streaming Gemini request to: {}", url);
+
+        let response = send_with_retry(RetryPolicy::from_config(&self.config), "Gemini", || {
+            self.client
+                .post(&url)
+                .header("x-goog-api-key", &self.config.gemini_api_key)
+                .header("Content-Type", "application/json")
+                .json(&request_body)
+        })
+        .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            log::error!(
+                "Gemini streaming request failed: Status: {}, Error: {}",
+                status,
+                error_text
+            );
+            return Err(ProviderError::status(
+                status,
+                format!("Gemini API request failed ({}): {}", status, error_text),
+            ));
+        }
+
+        Ok(response)
+    }
+
+    fn extract_text_content(response: &Value) -> Result<String> {
+        if let Some(block_reason) = response["promptFeedback"]["blockReason"].as_str() {
+            return Err(ProviderError::blocked(format!(
+                "Gemini blocked the request: {}",
+                block_reason
+            )));
+        }
+
+        let candidate = response["candidates"]
+            .as_array()
+            .and_then(|candidates| candidates.first())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No candidates in response. Full response: {}",
+                    serde_json::to_string_pretty(response).unwrap_or_default()
+                )
+            })?;
+
+        if is_max_tokens_finish(response) {
+            log::warn!("Gemini response was cut off at maxOutputTokens");
+            return Err(truncated_reply_error());
+        }
+
+        let content = candidate["content"]["parts"]
+            .as_array()
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(|part| part["text"].as_str())
+                    .collect::<String>()
+            })
+            .unwrap_or_default();
+
+        if content.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No text content in response. Full response: {}",
+                serde_json::to_string_pretty(response).unwrap_or_default()
+            ));
+        }
+
+        Ok(content)
+    }
+}
+
+fn extract_candidate_text(event: &Value) -> Option<&str> {
+    event
+        .get("candidates")?
+        .as_array()?
+        .first()?
+        .get("content")?
+        .get("parts")?
+        .as_array()?
+        .first()?
+        .get("text")?
+        .as_str()
+}
+
+// Streamed chunks carry the finish reason in the same place as complete responses
+fn is_max_tokens_finish(response: &Value) -> bool {
+    response["candidates"][0]["finishReason"].as_str() == Some("MAX_TOKENS")
+}
+
+#[async_trait]
+impl TranslationProvider for GeminiTranslationService {
+    fn capabilities(&self) -> ProviderCapabilities {
+        let settings = self.chat_settings();
+        ProviderCapabilities {
+            alternatives: true,
+            phrase_alternatives: true,
+            streaming: true,
+            language_detection: true,
+            max_input_chars: None,
+            max_chunk_chars: Some(chunking::chars_for_output_tokens(settings.reply_tokens())),
+            transliteration: true,
+            structured_output: false, // JSON mode only, without a schema
+            reasoning_effort: false,
+        }
+    }
+
+    async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResult> {
+        let request_body = self.build_request_body(request)?;
+
+        log::info!("Using Gemini model: {}", self.config.model);
+
+        let response = self.call_gemini(request_body).await?;
+        let content = Self::extract_text_content(&response)?;
+        log::info!("API Response content: {}", content);
+
+        if request.is_alternatives() {
+            // For alternatives requests, return the raw content as translated_text
+            // The calling code will parse the JSON alternatives from it
+            log::info!("Returning raw alternatives response");
+            Ok(TranslationResult {
+                detected_language: "unknown".to_string(),
+                translated_text: content,
+                target_language: "alternatives".to_string(),
+                transliteration: None,
+            })
+        } else {
+            Ok(parse_translation_json(&content, &request.target_language))
+        }
+    }
+
+    async fn translate_streaming(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: ChunkSink,
+    ) -> Result<TranslationResult> {
+        // Alternatives are parsed as a whole by the caller, so there is nothing to stream
+        if request.is_alternatives() {
+            return self.translate(request).await;
+        }
+
+        let request_body = self.build_request_body(request)?;
+        log::info!("Streaming with Gemini model: {}", self.config.model);
+
+        let response = self.call_gemini_stream(request_body).await?;
+        let content = read_sse_stream(
+            response,
+            &on_chunk,
+            extract_candidate_text,
+            is_max_tokens_finish,
+        )
+        .await?;
+        if content.is_empty() {
+            return Err(anyhow::anyhow!("Empty streamed response from Gemini"));
+        }
+
+        Ok(parse_translation_json(&content, &request.target_language))
+    }
+}
+
+/// Whether the model thinks by default: Gemini 2.5 and later, except the Flash-Lite models.
+fn is_thinking_model(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let name = name.strip_prefix("models/").unwrap_or(&name);
+    let Some(version) = name.strip_prefix("gemini-") else {
+        return false;
+    };
+    let major_minor: Vec<u32> = version
+        .split(['-', '.'])
+        .take(2)
+        .map_while(|part| part.parse().ok())
+        .collect();
+    let is_recent = match major_minor.as_slice() {
+        [major, minor] => (*major, *minor) >= (2, 5),
+        [major] => *major >= 3,
+        _ => false,
+    };
+    is_recent && !name.contains("flash-lite")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_tokens_finish_is_detected() {
+        let chunk = |finish_reason: Value| {
+            json!({
+                "candidates": [{
+                    "content": {"parts": [{"text": "{\"translated_text\": \"Hol"}]},
+                    "finishReason": finish_reason
+                }]
+            })
+        };
+        assert!(is_max_tokens_finish(&chunk("MAX_TOKENS".into())));
+        assert!(!is_max_tokens_finish(&chunk("STOP".into())));
+        assert!(!is_max_tokens_finish(&chunk(Value::Null)));
+        assert!(!is_max_tokens_finish(&json!({"promptFeedback": {}})));
+    }
+
+    #[test]
+    fn test_thinking_models_are_detected_by_version() {
+        assert!(is_thinking_model("gemini-2.5-flash"));
+        assert!(is_thinking_model("models/gemini-2.5-pro"));
+        assert!(is_thinking_model("gemini-3-pro-preview"));
+        assert!(!is_thinking_model("gemini-2.5-flash-lite"));
+        assert!(!is_thinking_model("gemini-2.0-flash"));
+        assert!(!is_thinking_model("gemini-1.5-pro"));
+        assert!(!is_thinking_model("gemma-3-27b-it"));
+    }
+}
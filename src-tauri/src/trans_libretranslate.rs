@@ -0,0 +1,263 @@
+use crate::config::Config;
+use crate::languages::{self, ProviderLanguage};
+use crate::retry::{RetryPolicy, send_with_retry};
+use crate::translation::{
+    ProviderCapabilities, ProviderError, TranslationProvider, TranslationRequest,
+    TranslationResult, clean_text_for_translation,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Deserialize)]
+struct LibreLanguage {
+    code: String,
+    name: String,
+}
+
+lazy_static! {
+    // `/languages` only changes when the server's models change, so fetch it once per server URL
+    static ref LANGUAGE_CACHE: Mutex<HashMap<String, Vec<LibreLanguage>>> =
+        Mutex::new(HashMap::new());
+}
+
+pub struct LibreTranslateService {
+    client: reqwest::Client,
+    config: Config,
+}
+
+impl LibreTranslateService {
+    pub fn new(config: Config) -> Self {
+        log::info!(
+            "Creating LibreTranslateService with URL: {}",
+            config.libretranslate_url
+        );
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    fn base_url(&self) -> Result<&str> {
+        let url = self.config.libretranslate_url.trim().trim_end_matches('/');
+        if url.is_empty() {
+            return Err(ProviderError::not_configured(
+                "LibreTranslate URL is not configured",
+            ));
+        }
+        Ok(url)
+    }
+
+    /// Adds the API key to a request body when one is configured; open instances need none.
+    fn with_api_key(&self, mut body: Value) -> Value {
+        if !self.config.libretranslate_api_key.trim().is_empty() {
+            body["api_key"] = json!(self.config.libretranslate_api_key.trim());
+        }
+        body
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<Value> {
+        let url = format!("{}{}", self.base_url()?, path);
+
+        log::info!("Making LibreTranslate request to: {}", url);
+
+        let body = self.with_api_key(body);
+        let response = send_with_retry(
+            RetryPolicy::from_config(&self.config),
+            "LibreTranslate",
+            || {
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            },
+        )
+        .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            log::error!(
+                "LibreTranslate request failed: Status: {}, Error: {}",
+                status,
+                error_text
+            );
+            return Err(ProviderError::status(
+                status,
+                format!("LibreTranslate request failed ({}): {}", status, error_text),
+            ));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    async fn languages(&self) -> Vec<LibreLanguage> {
+        let Ok(base_url) = self.base_url() else {
+            return Vec::new();
+        };
+
+        if let Ok(cache) = LANGUAGE_CACHE.lock()
+            && let Some(languages) = cache.get(base_url)
+        {
+            return languages.clone();
+        }
+
+        let url = format!("{}/languages", base_url);
+        let languages = match self.client.get(&url).send().await {
+            Ok(response) if response.status().is_success() => response
+                .json::<Vec<LibreLanguage>>()
+                .await
+                .unwrap_or_default(),
+            Ok(response) => {
+                log::warn!(
+                    "Failed to load LibreTranslate languages: Status: {}",
+                    response.status()
+                );
+                Vec::new()
+            }
+            Err(e) => {
+                log::warn!("Failed to load LibreTranslate languages: {}", e);
+                Vec::new()
+            }
+        };
+
+        if !languages.is_empty()
+            && let Ok(mut cache) = LANGUAGE_CACHE.lock()
+        {
+            cache.insert(base_url.to_string(), languages.clone());
+        }
+
+        languages
+    }
+
+    async fn detect_language(&self, text: &str) -> Result<String> {
+        let response = self.post("/detect", json!({ "q": text })).await?;
+
+        // Candidates are sorted by confidence, highest first
+        let detected = response
+            .as_array()
+            .and_then(|candidates| candidates.first())
+            .and_then(|candidate| candidate.get("language"))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Empty response from LibreTranslate for detection"))?;
+
+        Ok(detected.to_string())
+    }
+
+    /// The server's languages, for the language catalogue. Empty when it cannot be reached.
+    pub async fn supported_languages(&self) -> Vec<ProviderLanguage> {
+        self.languages()
+            .await
+            .into_iter()
+            .map(|language| ProviderLanguage {
+                code: language.code,
+                name: language.name,
+                native_name: None,
+            })
+            .collect()
+    }
+
+    fn map_language_code_to_tag(code: &str) -> String {
+        languages::tag(code)
+    }
+
+    fn map_language_tag_to_code(tag: &str) -> String {
+        languages::provider_code("libretranslate", tag).unwrap_or_else(|| tag.to_string())
+    }
+}
+
+#[async_trait]
+impl TranslationProvider for LibreTranslateService {
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            alternatives: false,
+            phrase_alternatives: false,
+            streaming: false,
+            language_detection: true,
+            max_input_chars: None, // Set per server (`--char-limit`), unlimited by default
+            max_chunk_chars: None,
+            transliteration: false,
+            structured_output: false,
+            reasoning_effort: false,
+        }
+    }
+
+    async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResult> {
+        if request.is_alternatives() {
+            return Err(anyhow::anyhow!(
+                "LibreTranslate cannot generate alternatives"
+            ));
+        }
+
+        let cleaned_text = clean_text_for_translation(&request.text);
+        log::info!("Cleaned text for translation: {}", cleaned_text);
+
+        // Check if user specified a source language override
+        let source_language_code = request
+            .source_language
+            .as_ref()
+            .map(|lang| Self::map_language_tag_to_code(lang));
+
+        // First, detect the language if not specified by user
+        let detected_language = match &source_language_code {
+            Some(code) => code.clone(),
+            None => self.detect_language(&cleaned_text).await?,
+        };
+
+        log::info!("Detected language code: {}", detected_language);
+
+        // Determine the actual target language based on smart switching logic
+        // Variants of the primary target ("pt" for "pt-BR") count as a match
+        let target_language = if languages::same_base_language(
+            &detected_language,
+            &request.target_language,
+        ) {
+            log::info!(
+                "Smart switching: detected '{}' matches primary target '{}', using alternative target '{}'",
+                detected_language,
+                request.target_language,
+                request.alternative_target_language
+            );
+            &request.alternative_target_language
+        } else {
+            &request.target_language
+        };
+        let target_language_code = Self::map_language_tag_to_code(target_language);
+
+        log::info!("Final target language code: {}", target_language_code);
+
+        let response = self
+            .post(
+                "/translate",
+                json!({
+                    "q": cleaned_text,
+                    "source": detected_language,
+                    "target": target_language_code,
+                    "format": "text"
+                }),
+            )
+            .await?;
+
+        let translated_text = response
+            .get("translatedText")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No translation text found. Full response: {}",
+                    serde_json::to_string_pretty(&response).unwrap_or_default()
+                )
+            })?
+            .to_string();
+
+        Ok(TranslationResult {
+            detected_language: Self::map_language_code_to_tag(&detected_language),
+            translated_text,
+            target_language: target_language.clone(),
+            transliteration: None,
+        })
+    }
+}
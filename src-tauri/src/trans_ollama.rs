@@ -1,17 +1,54 @@
+use crate::chunking;
 use crate::config::Config;
+use crate::languages;
+use crate::models::ProviderModel;
+use crate::retry::{RetryPolicy, retry};
+use crate::streaming::extract_partial_json_string;
 use crate::translation::{
-    TranslationProvider, TranslationResult, clean_text_for_translation, create_smart_prompt,
+    ChunkSink, ProviderCapabilities, ProviderError, TranslationProvider, TranslationRequest,
+    TranslationResult, alternatives_json_schema, clean_text_for_translation,
+    create_alternatives_prompt, create_smart_prompt, parse_translation_json,
+    translation_json_schema, truncated_reply_error,
 };
 use anyhow::Result;
 use async_trait::async_trait;
+use futures_util::StreamExt;
 use ollama_rs::{
     Ollama,
-    generation::completion::{GenerationResponse, request::GenerationRequest},
+    error::OllamaError,
+    generation::{
+        completion::{GenerationResponse, request::GenerationRequest},
+        parameters::{FormatType, JsonStructure, KeepAlive, TimeUnit},
+    },
+    models::ModelOptions,
 };
+use serde::Serialize;
 use serde_json::{Value, json};
 
+/// Progress of a model download, emitted to the frontend as `ollama-pull-progress`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PullProgress {
+    pub model: String,
+    pub status: String, // e.g. "pulling manifest", "downloading", "success"
+    pub completed: Option<u64>, // Bytes of the current layer, while downloading
+    pub total: Option<u64>,
+}
+
+/// Size and quantization of an installed model.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OllamaModelDetails {
+    pub name: String,
+    pub size: Option<u64>, // Bytes on disk
+    pub family: Option<String>,
+    pub format: Option<String>,
+    pub parameter_size: Option<String>,     // e.g. "8.0B"
+    pub quantization_level: Option<String>, // e.g. "Q4_K_M"
+}
+
 pub struct OllamaTranslationService {
     client: Ollama,
+    http: reqwest::Client,
+    base_url: String,
     config: Config,
 }
 
@@ -54,201 +91,293 @@ impl OllamaTranslationService {
 
         log::info!("Parsed Ollama host: {}, port: {}", host, port);
 
+        let base_url = format!("{}:{}", host, port);
         let client = Ollama::new(host, port);
 
-        Self { client, config }
+        Self {
+            client,
+            http: reqwest::Client::new(),
+            base_url,
+            config,
+        }
     }
 
-    fn parse_response_content(&self, content: &str) -> Result<TranslationResult> {
-        log::info!("Ollama Response content: {}", content);
+    /// Downloads a model, reporting each status update Ollama sends.
+    pub async fn pull_model(&self, model: &str, on_progress: impl Fn(PullProgress)) -> Result<()> {
+        log::info!("Pulling Ollama model: {}", model);
+        let mut stream = self
+            .client
+            .pull_model_stream(model.to_string(), false)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to pull '{}': {}", model, e))?;
+
+        while let Some(status) = stream.next().await {
+            let status =
+                status.map_err(|e| anyhow::anyhow!("Failed to pull '{}': {}", model, e))?;
+            on_progress(PullProgress {
+                model: model.to_string(),
+                status: status.message,
+                completed: status.completed,
+                total: status.total,
+            });
+        }
+
+        log::info!("Pulled Ollama model: {}", model);
+        Ok(())
+    }
 
-        // Clean the content by removing control characters that can break JSON parsing
-        let cleaned_content = content
-            .chars()
-            .filter(|c| !c.is_control() || matches!(*c, '\n' | '\r' | '\t'))
-            .collect::<String>();
+    pub async fn delete_model(&self, model: &str) -> Result<()> {
+        log::info!("Deleting Ollama model: {}", model);
+        self.client
+            .delete_model(model.to_string())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to delete '{}': {}", model, e))
+    }
 
-        // Log if we removed any control characters
-        if cleaned_content != content {
-            log::warn!("Removed control characters from Ollama response");
-            log::info!("Cleaned content: {}", cleaned_content);
+    /// Size and quantization of an installed model. ollama-rs' `show_model_info` leaves out the
+    /// `details` object of `/api/show`, which holds the quantization, so it is read directly.
+    pub async fn show_model(&self, model: &str) -> Result<OllamaModelDetails> {
+        let url = format!("{}/api/show", self.base_url);
+        let response = self
+            .http
+            .post(&url)
+            .json(&json!({ "model": model }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!(
+                "Ollama show request failed ({}): {}",
+                status,
+                error_text
+            ));
         }
 
-        // Try to parse as JSON, but handle cases where the AI might have returned plain text
-        let parsed: Value = match serde_json::from_str(&cleaned_content) {
-            Ok(json) => {
-                log::info!("Successfully parsed JSON response from Ollama");
-                json
-            }
-            Err(parse_error) => {
-                log::warn!("Failed to parse Ollama response as JSON: {}", parse_error);
-
-                // Try to find and extract valid JSON from the response
-                if let Some(start_idx) = cleaned_content.find('{') {
-                    // Find the matching closing brace by counting braces
-                    let mut brace_count = 0;
-                    let mut end_idx = None;
-
-                    for (i, c) in cleaned_content[start_idx..].char_indices() {
-                        if c == '{' {
-                            brace_count += 1;
-                        } else if c == '}' {
-                            brace_count -= 1;
-                            if brace_count == 0 {
-                                end_idx = Some(start_idx + i + 1); // +1 to include the closing brace
-                                break;
-                            }
-                        }
-                    }
-
-                    if let Some(end_idx) = end_idx {
-                        let json_str = &cleaned_content[start_idx..end_idx];
-                        log::info!(
-                            "Attempting to parse extracted JSON from Ollama: {}",
-                            json_str
-                        );
-
-                        match serde_json::from_str::<Value>(json_str) {
-                            Ok(json) => {
-                                log::info!("Successfully parsed extracted JSON from Ollama");
-                                json
-                            }
-                            Err(e) => {
-                                log::warn!("Failed to parse extracted JSON from Ollama: {}", e);
-                                json!({
-                                    "detected_language": "unknown",
-                                    "translated_text": cleaned_content
-                                })
-                            }
-                        }
-                    } else {
-                        log::warn!("Could not find matching closing brace in Ollama response");
-                        json!({
-                            "detected_language": "unknown",
-                            "translated_text": cleaned_content
-                        })
-                    }
-                } else {
-                    log::warn!("No JSON structure found in Ollama response");
-                    json!({
-                        "detected_language": "unknown",
-                        "translated_text": cleaned_content
-                    })
-                }
-            }
-        };
+        let body: Value = response.json().await?;
+        let detail = |key: &str| body["details"][key].as_str().map(str::to_string);
 
-        // Extract detected language and translated text from parsed JSON
-        let detected_language = match parsed["detected_language"].as_str() {
-            Some(lang) if !lang.is_empty() => lang.to_string(),
-            _ => {
-                // If detected_language field is missing or empty, try to look deeper in JSON structure
-                if let Some(lang) = parsed.get("detected_language").and_then(|v| v.as_str()) {
-                    lang.to_string()
-                } else {
-                    "unknown".to_string()
-                }
+        // `/api/show` has no file size; the local model list does
+        let size = self
+            .client
+            .list_local_models()
+            .await
+            .ok()
+            .and_then(|models| models.into_iter().find(|m| m.name == model))
+            .map(|m| m.size);
+
+        Ok(OllamaModelDetails {
+            name: model.to_string(),
+            size,
+            family: detail("family"),
+            format: detail("format"),
+            parameter_size: detail("parameter_size"),
+            quantization_level: detail("quantization_level"),
+        })
+    }
+
+    /// A missing model is the most common cause of failed generations, so name it explicitly.
+    fn generation_error(&self, error: OllamaError) -> anyhow::Error {
+        let message = error.to_string();
+        if message.contains("not found") {
+            anyhow::anyhow!(
+                "Ollama model '{}' is not installed. Pull it under Settings > Models.",
+                self.config.model
+            )
+        } else {
+            // Keep the error as the source, so an unreachable server is recognised for failover
+            anyhow::Error::new(error).context(format!("Ollama generation failed: {}", message))
+        }
+    }
+
+    /// Temperature, context window and output limit from the model's list entry.
+    fn model_options(&self) -> ModelOptions {
+        let mut options = ModelOptions::default();
+        if let Some(model) = self.config.model_config("ollama", &self.config.model) {
+            if let Some(temperature) = model.temperature {
+                options = options.temperature(temperature);
             }
-        };
-        let translated_text = match parsed["translated_text"].as_str() {
-            Some(text) => {
-                // Unescape any escaped newlines that Ollama might have returned
-                text.replace("\\n", "\n")
-                    .replace("/n", "\n") // Also handle the literal /n issue
-                    .replace("\\r\\n", "\n")
-                    .replace("\\r", "\n")
+            if let Some(context_window) = model.context_window {
+                options = options.num_ctx(context_window as u64);
             }
-            None => {
-                // If translated_text field is missing, check if the whole response is just text
-                if parsed.is_string() {
-                    let text = parsed.as_str().unwrap_or("translation failed");
-                    text.replace("\\n", "\n")
-                        .replace("/n", "\n") // Also handle the literal /n issue
-                        .replace("\\r\\n", "\n")
-                        .replace("\\r", "\n")
-                } else {
-                    "translation failed".to_string()
-                }
+            if let Some(max_output_tokens) = model.max_output_tokens {
+                options = options.num_predict(max_output_tokens as i32);
             }
-        };
-        log::info!(
-            "Detected language (provider reported): {}",
-            detected_language
-        );
-        log::info!(
-            "Configured (effective) target language: {}",
-            self.config.target_language
-        );
+        }
+        options
+    }
 
-        log::info!(
-            "Translated text (first 100 chars): {}",
-            if translated_text.len() > 100 {
-                format!("{}...", &translated_text[..100])
-            } else {
-                translated_text.clone()
-            }
-        );
+    /// Whether a generation stopped at the `num_predict` limit from the model's list entry.
+    fn is_cut_off(&self, response: &GenerationResponse) -> bool {
+        let num_predict = self
+            .config
+            .model_config("ollama", &self.config.model)
+            .and_then(|m| m.max_output_tokens);
+        reached_num_predict(response.eval_count.map(u64::from), num_predict)
+    }
 
-        Ok(TranslationResult {
-            detected_language,
-            translated_text,
-            target_language: self.config.target_language.clone(),
-        })
+    /// How long Ollama keeps the model loaded after a request, from the model's list entry.
+    fn keep_alive(&self) -> Option<KeepAlive> {
+        let value = self
+            .config
+            .model_config("ollama", &self.config.model)?
+            .keep_alive
+            .as_deref()?;
+        let keep_alive = parse_keep_alive(value);
+        if keep_alive.is_none() {
+            log::warn!("Ignoring invalid Ollama keep-alive '{}'", value);
+        }
+        keep_alive
     }
-}
 
-#[async_trait]
-impl TranslationProvider for OllamaTranslationService {
-    async fn translate(&self, text: &str) -> Result<TranslationResult> {
-        let cleaned_text = clean_text_for_translation(text);
+    /// Loads the model into memory with an empty prompt, so the next translation does not have
+    /// to wait for it.
+    pub async fn warm_up(&self) -> Result<()> {
+        if self.config.model.trim().is_empty() {
+            return Ok(());
+        }
+
+        log::info!("Warming up Ollama model: {}", self.config.model);
+        let mut generation_request =
+            GenerationRequest::new(self.config.model.clone(), String::new())
+                .options(self.model_options());
+        if let Some(keep_alive) = self.keep_alive() {
+            generation_request = generation_request.keep_alive(keep_alive);
+        }
+
+        self.client
+            .generate(generation_request)
+            .await
+            .map_err(|e| self.generation_error(e))?;
+        log::info!("Ollama model {} is loaded", self.config.model);
+        Ok(())
+    }
+
+    /// The locally installed models, from `/api/tags`.
+    pub async fn list_models(&self) -> Result<Vec<ProviderModel>> {
+        let models = self
+            .client
+            .list_local_models()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list Ollama models: {}", e))?;
+
+        Ok(models
+            .into_iter()
+            .map(|model| ProviderModel {
+                name: model.name,
+                description: Some(format!("{:.1} GB on disk", model.size as f64 / 1e9)),
+            })
+            .collect())
+    }
+
+    /// Constrains generation to the reply schema (Ollama 0.5 or later).
+    fn response_format(&self, request: &TranslationRequest) -> Result<FormatType> {
+        let schema = if request.is_alternatives() {
+            alternatives_json_schema()
+        } else {
+            translation_json_schema()
+        };
+        let schema = schemars::Schema::try_from(schema)
+            .map_err(|e| anyhow::anyhow!("Invalid response schema: {}", e))?;
+
+        Ok(FormatType::StructuredJson(Box::new(
+            JsonStructure::new_for_schema(schema),
+        )))
+    }
+
+    fn build_prompt(&self, request: &TranslationRequest) -> Result<String> {
+        let cleaned_text = clean_text_for_translation(&request.text);
         log::info!("Cleaned text for Ollama translation: {}", cleaned_text);
 
         if self.config.model.trim().is_empty() {
             log::error!("OllamaTranslationService: model is empty; cannot proceed");
-            return Err(anyhow::anyhow!("Model not configured for Ollama provider"));
+            return Err(ProviderError::not_configured(
+                "Model not configured for Ollama provider",
+            ));
         }
 
-        // Check if this is an alternatives request (custom_prompt contains "alternatives")
-        let is_alternatives_request = self.config.custom_prompt.contains("alternatives");
+        let is_alternatives_request = request.is_alternatives();
 
         let full_prompt = if is_alternatives_request {
-            // For alternatives requests, use the custom prompt directly
             log::info!("Using alternatives prompt for Ollama");
-            format!("{}\n\n\"{}\"", self.config.custom_prompt, cleaned_text)
+            format!(
+                "{}\n\n\"{}\"",
+                create_alternatives_prompt(request),
+                cleaned_text
+            )
         } else {
             // For regular translations, use the normal logic
             let user_prompt = format!(
                 "Text to translate into {}: \"{}\"",
-                self.config.target_language, cleaned_text
+                languages::english_name(&request.target_language),
+                cleaned_text
             );
-            let smart_prompt = create_smart_prompt(&self.config, None);
+            let smart_prompt = create_smart_prompt(&self.config, request);
             format!(
                 "{}\n\nAlways respond with valid JSON containing 'detected_language' and 'translated_text' fields. Preserve line breaks and formatting in the translated text.\n\n{}",
                 smart_prompt, user_prompt
             )
         };
 
+        Ok(full_prompt)
+    }
+}
+
+#[async_trait]
+impl TranslationProvider for OllamaTranslationService {
+    fn capabilities(&self) -> ProviderCapabilities {
+        // Without an output limit, the prompt, the text and the reply share the context window
+        // (2048 tokens unless configured)
+        let model = self.config.model_config("ollama", &self.config.model);
+        let reply_tokens = model
+            .and_then(|m| m.max_output_tokens)
+            .unwrap_or_else(|| model.and_then(|m| m.context_window).unwrap_or(2048) / 3);
+        ProviderCapabilities {
+            alternatives: true,
+            phrase_alternatives: true,
+            streaming: true,
+            language_detection: true,
+            max_input_chars: None,
+            max_chunk_chars: Some(chunking::chars_for_output_tokens(reply_tokens)),
+            transliteration: true,
+            structured_output: true,
+            reasoning_effort: false,
+        }
+    }
+
+    async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResult> {
+        let full_prompt = self.build_prompt(request)?;
+
         log::info!("Using Ollama model: {}", self.config.model);
         log::info!("Full prompt for Ollama: {}", full_prompt);
 
-        // NOTE: GenerationOptions API removed/changed in ollama-rs 0.3.x; using basic request.
-        // If advanced tuning is needed, update to the new ModelOptions once identified.
-        let request = GenerationRequest::new(self.config.model.clone(), full_prompt);
+        let mut generation_request = GenerationRequest::new(self.config.model.clone(), full_prompt)
+            .format(self.response_format(request)?)
+            .options(self.model_options());
+        if let Some(keep_alive) = self.keep_alive() {
+            generation_request = generation_request.keep_alive(keep_alive);
+        }
 
-        let response: GenerationResponse = self
-            .client
-            .generate(request)
-            .await
-            .map_err(|e| anyhow::anyhow!("Ollama generation failed: {}", e))?;
+        let response: GenerationResponse = retry(
+            RetryPolicy::from_config(&self.config),
+            "Ollama",
+            is_transient_error,
+            || self.client.generate(generation_request.clone()),
+        )
+        .await
+        .map_err(|e| self.generation_error(e))?;
+
+        if self.is_cut_off(&response) {
+            log::warn!("Ollama response was cut off at num_predict");
+            return Err(truncated_reply_error());
+        }
 
         let content = response.response;
         if content.is_empty() {
             return Err(anyhow::anyhow!("Empty response from Ollama"));
         }
 
-        // Check if this is an alternatives request
-        let is_alternatives_request = self.config.custom_prompt.contains("alternatives");
-        if is_alternatives_request {
+        if request.is_alternatives() {
             // For alternatives requests, return the raw content as translated_text
             // The calling code will parse the JSON alternatives from it
             log::info!("Returning raw alternatives response from Ollama");
@@ -256,9 +385,150 @@ impl TranslationProvider for OllamaTranslationService {
                 detected_language: "unknown".to_string(),
                 translated_text: content,
                 target_language: "alternatives".to_string(),
+                transliteration: None,
             })
         } else {
-            self.parse_response_content(&content)
+            log::info!("Ollama Response content: {}", content);
+            Ok(parse_translation_json(&content, &request.target_language))
+        }
+    }
+
+    async fn translate_streaming(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: ChunkSink,
+    ) -> Result<TranslationResult> {
+        // Alternatives are parsed as a whole by the caller, so there is nothing to stream
+        if request.is_alternatives() {
+            return self.translate(request).await;
+        }
+
+        let full_prompt = self.build_prompt(request)?;
+        log::info!("Streaming with Ollama model: {}", self.config.model);
+
+        let mut generation_request = GenerationRequest::new(self.config.model.clone(), full_prompt)
+            .format(self.response_format(request)?)
+            .options(self.model_options());
+        if let Some(keep_alive) = self.keep_alive() {
+            generation_request = generation_request.keep_alive(keep_alive);
+        }
+        let mut stream = retry(
+            RetryPolicy::from_config(&self.config),
+            "Ollama",
+            is_transient_error,
+            || self.client.generate_stream(generation_request.clone()),
+        )
+        .await
+        .map_err(|e| self.generation_error(e))?;
+
+        // Dropping the stream (e.g. when the translation is cancelled) closes the connection,
+        // which makes Ollama stop generating.
+        let mut content = String::new();
+        let mut last_emitted = String::new();
+        let mut truncated = false;
+        while let Some(chunk) = stream.next().await {
+            let responses = chunk.map_err(|e| anyhow::anyhow!("Ollama stream failed: {}", e))?;
+            for response in responses {
+                truncated |= self.is_cut_off(&response);
+                content.push_str(&response.response);
+            }
+
+            if let Some(partial) = extract_partial_json_string(&content, "translated_text")
+                && partial != last_emitted
+            {
+                on_chunk(&partial);
+                last_emitted = partial;
+            }
+        }
+
+        if truncated {
+            log::warn!("Ollama stream was cut off at num_predict");
+            return Err(truncated_reply_error());
         }
+        if content.is_empty() {
+            return Err(anyhow::anyhow!("Empty response from Ollama"));
+        }
+
+        // The JSON envelope is only complete once the stream has ended
+        Ok(parse_translation_json(&content, &request.target_language))
+    }
+}
+
+/// Parses a keep-alive the way Ollama writes it: a negative number keeps the model loaded,
+/// "0" unloads it right after the request, and "30s", "10m" or "1h" keep it that long.
+/// A bare number counts seconds.
+fn parse_keep_alive(value: &str) -> Option<KeepAlive> {
+    let value = value.trim();
+    if let Some(number) = value.strip_prefix('-') {
+        let is_number = !number.is_empty() && number.chars().all(|c| c.is_ascii_digit());
+        return is_number.then_some(KeepAlive::Indefinitely);
+    }
+
+    let (number, unit) = match value.char_indices().last()? {
+        (i, 's') => (&value[..i], TimeUnit::Seconds),
+        (i, 'm') => (&value[..i], TimeUnit::Minutes),
+        (i, 'h') => (&value[..i], TimeUnit::Hours),
+        _ => (value, TimeUnit::Seconds),
+    };
+    match number.parse::<u64>().ok()? {
+        0 => Some(KeepAlive::UnloadOnCompletion),
+        time => Some(KeepAlive::Until { time, unit }),
+    }
+}
+
+/// Whether a generation stopped because it used up `num_predict`. Ollama does not say why it
+/// stopped, so this compares the tokens generated with the limit that was sent.
+fn reached_num_predict(eval_count: Option<u64>, num_predict: Option<u32>) -> bool {
+    match (eval_count, num_predict) {
+        (Some(eval_count), Some(num_predict)) => eval_count >= u64::from(num_predict),
+        _ => false,
+    }
+}
+
+/// Connection failures and timeouts, e.g. while the Ollama server is still starting up.
+fn is_transient_error(error: &OllamaError) -> bool {
+    matches!(error, OllamaError::ReqwestError(e) if e.is_connect() || e.is_timeout())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reaching_num_predict_is_cut_off() {
+        assert!(reached_num_predict(Some(800), Some(800)));
+        assert!(!reached_num_predict(Some(799), Some(800)));
+        // Without a limit Ollama stops on its own
+        assert!(!reached_num_predict(Some(5000), None));
+        // Only the final response of a stream carries the count
+        assert!(!reached_num_predict(None, Some(800)));
+    }
+
+    #[test]
+    fn test_parse_keep_alive() {
+        assert!(matches!(
+            parse_keep_alive("10m"),
+            Some(KeepAlive::Until {
+                time: 10,
+                unit: TimeUnit::Minutes
+            })
+        ));
+        assert!(matches!(
+            parse_keep_alive("90"),
+            Some(KeepAlive::Until {
+                time: 90,
+                unit: TimeUnit::Seconds
+            })
+        ));
+        assert!(matches!(
+            parse_keep_alive("-1"),
+            Some(KeepAlive::Indefinitely)
+        ));
+        assert!(matches!(
+            parse_keep_alive("0"),
+            Some(KeepAlive::UnloadOnCompletion)
+        ));
+        assert!(parse_keep_alive("soon").is_none());
+        assert!(parse_keep_alive("").is_none());
     }
 }
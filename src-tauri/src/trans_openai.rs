@@ -1,14 +1,28 @@
+use crate::chunking;
 use crate::config::Config;
+use crate::languages;
+use crate::models::{self, ChatModelSettings, ProviderModel};
+use crate::retry::{RetryPolicy, send_with_retry};
+use crate::streaming::read_chat_completion_stream;
 use crate::translation::{
-    TranslationProvider, TranslationResult, clean_text_for_translation, create_smart_prompt,
+    ChunkSink, ProviderCapabilities, ProviderError, TranslationProvider, TranslationRequest,
+    TranslationResult, clean_text_for_translation, create_alternatives_prompt, create_smart_prompt,
+    json_schema_response_format, parse_translation_json, truncated_reply_error,
 };
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::{Value, json};
 
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
 pub struct OpenAITranslationService {
     client: reqwest::Client,
     config: Config,
+    provider: &'static str, // Key of the model list holding the model's settings
+    base_url: String,
+    api_key: String,
+    // Compatible servers differ in `json_schema` support, so only api.openai.com gets it
+    structured_output: bool,
 }
 
 impl OpenAITranslationService {
@@ -17,19 +31,105 @@ impl OpenAITranslationService {
             "Creating OpenAITranslationService with model: {}",
             config.model
         );
+        let api_key = config.openai_api_key.clone();
+        Self {
+            client: reqwest::Client::new(),
+            config,
+            provider: "openai",
+            base_url: OPENAI_BASE_URL.to_string(),
+            api_key,
+            structured_output: true,
+        }
+    }
+
+    /// Creates a service for any server that implements the OpenAI chat completions API
+    /// (LM Studio, vLLM, llama.cpp server, LocalAI, OpenRouter, internal gateways, ...).
+    pub fn new_compatible(config: Config) -> Self {
+        let base_url = config
+            .openai_compatible_base_url
+            .trim()
+            .trim_end_matches('/')
+            .to_string();
+        log::info!(
+            "Creating OpenAI-compatible translation service with base URL: {}, model: {}",
+            base_url,
+            config.model
+        );
+        let api_key = config.openai_compatible_api_key.clone();
         Self {
             client: reqwest::Client::new(),
             config,
+            provider: "openai_compatible",
+            base_url,
+            api_key,
+            structured_output: false,
+        }
+    }
+
+    fn chat_completions_url(&self) -> String {
+        format!("{}/chat/completions", self.base_url)
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        // The key is optional for OpenAI-compatible servers running locally
+        if self.api_key.is_empty() {
+            request
+        } else {
+            request.header("Authorization", format!("Bearer {}", self.api_key))
+        }
+    }
+
+    /// The models `/models` lists. Compatible servers report the models they have loaded.
+    pub async fn list_models(&self) -> Result<Vec<ProviderModel>> {
+        let url = format!("{}/models", self.base_url);
+        log::info!("Listing models from: {}", url);
+
+        let response = self.authorize(self.client.get(&url)).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!(
+                "Model list request failed ({}): {}",
+                status,
+                error_text
+            ));
         }
+
+        let body: Value = response.json().await?;
+        Ok(body["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|model| {
+                let name = model["id"].as_str()?.to_string();
+                let created = model["created"]
+                    .as_i64()
+                    .and_then(|created| chrono::DateTime::from_timestamp(created, 0))
+                    .map(|created| format!("created {}", created.format("%Y-%m-%d")));
+                let owner = model["owned_by"]
+                    .as_str()
+                    .filter(|owner| !owner.is_empty())
+                    .map(|owner| format!("by {}", owner));
+                let description = [owner, created].into_iter().flatten().collect::<Vec<_>>();
+                Some(ProviderModel {
+                    name,
+                    description: (!description.is_empty()).then(|| description.join(", ")),
+                })
+            })
+            .collect())
     }
 
-    fn is_reasoning_model(&self) -> bool {
-        // Automatic detection based on known reasoning model name patterns
-        is_reasoning_model_name(self.config.model.as_str())
+    fn chat_settings(&self) -> ChatModelSettings {
+        models::chat_settings(&self.config, self.provider, &self.config.model)
     }
 
     async fn call_openai(&self, request_body: Value) -> Result<Value> {
-        let url = "https://api.openai.com/v1/chat/completions";
+        if self.base_url.is_empty() {
+            return Err(ProviderError::not_configured(
+                "Base URL not configured for OpenAI-compatible provider",
+            ));
+        }
+        let url = self.chat_completions_url();
 
         log::info!("Making OpenAI request to: {}", url);
         log::info!(
@@ -39,21 +139,18 @@ impl OpenAITranslationService {
 
         let mut attempt_body = request_body.clone();
         for attempt in 1..=2 {
-            let response = self
-                .client
-                .post(url)
-                .header(
-                    "Authorization",
-                    format!("Bearer {}", self.config.openai_api_key),
-                )
-                .header("Content-Type", "application/json")
-                .json(&attempt_body)
-                .send()
+            let response =
+                send_with_retry(RetryPolicy::from_config(&self.config), "OpenAI", || {
+                    self.authorize(self.client.post(&url))
+                        .header("Content-Type", "application/json")
+                        .json(&attempt_body)
+                })
                 .await?;
 
             if response.status().is_success() {
                 return Ok(response.json().await?);
             }
+            let status = response.status();
             let error_text = response.text().await?;
             log::error!(
                 "OpenAI API request failed (attempt {}): {}",
@@ -66,204 +163,85 @@ impl OpenAITranslationService {
                 attempt_body["model"] = json!(self.config.model);
                 continue;
             } else {
-                return Err(anyhow::anyhow!("OpenAI API request failed: {}", error_text));
+                return Err(ProviderError::status(
+                    status,
+                    format!("OpenAI API request failed ({}): {}", status, error_text),
+                ));
             }
         }
         Err(anyhow::anyhow!("OpenAI API request failed after retries"))
     }
 
-    fn parse_response_content(&self, content: &str) -> Result<TranslationResult> {
-        log::info!("API Response content: {}", content);
-
-        // Clean the content by removing control characters that can break JSON parsing
-        let cleaned_content = content
-            .chars()
-            .filter(|c| !c.is_control() || matches!(*c, '\n' | '\r' | '\t'))
-            .collect::<String>();
-
-        // Log if we removed any control characters
-        if cleaned_content != content {
-            log::warn!("Removed control characters from API response");
-            log::info!("Cleaned content: {}", cleaned_content);
+    async fn call_openai_stream(&self, mut request_body: Value) -> Result<reqwest::Response> {
+        if self.base_url.is_empty() {
+            return Err(ProviderError::not_configured(
+                "Base URL not configured for OpenAI-compatible provider",
+            ));
         }
+        let url = self.chat_completions_url();
+        request_body["stream"] = json!(true);
 
-        // Try to parse as JSON, but handle cases where the AI might have returned plain text
-        let parsed: Value = match serde_json::from_str(&cleaned_content) {
-            Ok(json) => {
-                log::info!("Successfully parsed JSON response");
-                json
-            }
-            Err(parse_error) => {
-                log::warn!("Failed to parse as JSON: {}", parse_error);
-
-                // Try to find and extract valid JSON from the response
-                if let Some(start_idx) = cleaned_content.find('{') {
-                    // Find the matching closing brace by counting braces
-                    let mut brace_count = 0;
-                    let mut end_idx = None;
-
-                    for (i, c) in cleaned_content[start_idx..].char_indices() {
-                        if c == '{' {
-                            brace_count += 1;
-                        } else if c == '}' {
-                            brace_count -= 1;
-                            if brace_count == 0 {
-                                end_idx = Some(start_idx + i + 1); // +1 to include the closing brace
-                                break;
-                            }
-                        }
-                    }
-
-                    if let Some(end_idx) = end_idx {
-                        let json_str = &cleaned_content[start_idx..end_idx];
-                        log::info!("Attempting to parse extracted JSON: {}", json_str);
-
-                        match serde_json::from_str::<Value>(json_str) {
-                            Ok(json) => {
-                                log::info!("Successfully parsed extracted JSON");
-                                json
-                            }
-                            Err(e) => {
-                                log::warn!("Failed to parse extracted JSON: {}", e);
-                                json!({
-                                    "detected_language": "unknown",
-                                    "translated_text": cleaned_content
-                                })
-                            }
-                        }
-                    } else {
-                        log::warn!("Could not find matching closing brace");
-                        json!({
-                            "detected_language": "unknown",
-                            "translated_text": cleaned_content
-                        })
-                    }
-                } else {
-                    log::warn!("No JSON structure found in response");
-                    json!({
-                        "detected_language": "unknown",
-                        "translated_text": cleaned_content
-                    })
-                }
-            }
-        };
+        log::info!("Making streaming OpenAI request to: {}", url);
 
-        // Extract detected language and translated text from parsed JSON
-        let detected_language = match parsed["detected_language"].as_str() {
-            Some(lang) if !lang.is_empty() => lang.to_string(),
-            _ => {
-                // If detected_language field is missing or empty, try to look deeper in JSON structure
-                if let Some(lang) = parsed.get("detected_language").and_then(|v| v.as_str()) {
-                    lang.to_string()
-                } else {
-                    "unknown".to_string()
-                }
-            }
-        };
-        let translated_text = match parsed["translated_text"].as_str() {
-            Some(text) => {
-                // Properly handle escaped newlines that OpenAI might return
-                text.replace("\\n", "\n")
-                    .replace("\\r\\n", "\n")
-                    .replace("\\r", "\n")
-                    .replace("\\t", "\t")
-            }
-            None => {
-                // If translated_text field is missing, check if the whole response is just text
-                if parsed.is_string() {
-                    let text = parsed.as_str().unwrap_or("translation failed");
-                    text.replace("\\n", "\n")
-                        .replace("\\r\\n", "\n")
-                        .replace("\\r", "\n")
-                        .replace("\\t", "\t")
-                } else {
-                    "translation failed".to_string()
-                }
-            }
-        };
-        log::info!(
-            "Detected language (provider reported): {}",
-            detected_language
-        );
-        log::info!(
-            "Configured (effective) target language: {}",
-            self.config.target_language
-        );
+        let response = send_with_retry(RetryPolicy::from_config(&self.config), "OpenAI", || {
+            self.authorize(self.client.post(&url))
+                .header("Content-Type", "application/json")
+                .json(&request_body)
+        })
+        .await?;
 
-        log::info!(
-            "Translated text (first 100 chars): {}",
-            if translated_text.len() > 100 {
-                format!("{}...", &translated_text[..100])
-            } else {
-                translated_text.clone()
-            }
-        );
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            log::error!("OpenAI streaming request failed: {}", error_text);
+            return Err(ProviderError::status(
+                status,
+                format!("OpenAI API request failed ({}): {}", status, error_text),
+            ));
+        }
 
-        Ok(TranslationResult {
-            detected_language,
-            translated_text,
-            target_language: self.config.target_language.clone(),
-        })
+        Ok(response)
     }
-}
 
-#[async_trait]
-impl TranslationProvider for OpenAITranslationService {
-    async fn translate(&self, text: &str) -> Result<TranslationResult> {
-        let cleaned_text = clean_text_for_translation(text);
+    fn build_request_body(&self, request: &TranslationRequest) -> Result<Value> {
+        let cleaned_text = clean_text_for_translation(&request.text);
         log::info!("Cleaned text for translation: {}", cleaned_text);
 
         if self.config.model.trim().is_empty() {
             log::error!("OpenAITranslationService: model is empty; cannot proceed");
-            return Err(anyhow::anyhow!("Model not configured for OpenAI provider"));
+            return Err(ProviderError::not_configured(
+                "Model not configured for OpenAI provider",
+            ));
         }
 
-        // Check if this is an alternatives request (custom_prompt contains "alternatives")
-        let is_alternatives_request = self.config.custom_prompt.contains("alternatives");
+        let is_alternatives_request = request.is_alternatives();
 
         let (user_prompt, system_prompt) = if is_alternatives_request {
             // For alternatives requests, use the custom prompt directly as the system prompt
             log::info!("Using alternatives prompt");
             (
                 format!("\"{}\"", cleaned_text),
-                self.config.custom_prompt.clone(),
+                create_alternatives_prompt(request),
             )
         } else {
             // For regular translations, use the normal logic (target already resolved in config)
             let user_prompt = format!(
                 "Text to translate into {}: \"{}\"",
-                self.config.target_language, cleaned_text
+                languages::english_name(&request.target_language),
+                cleaned_text
             );
-            let smart_prompt = create_smart_prompt(&self.config, None);
+            let smart_prompt = create_smart_prompt(&self.config, request);
             log::info!(
                 "Using smart prompt (pre-resolved target '{}')",
-                self.config.target_language
+                request.target_language
             );
             (user_prompt, smart_prompt)
         };
 
-        // Check if current model is a reasoning model
-        let is_reasoning_model = self.is_reasoning_model();
-        log::info!(
-            "Model {} is reasoning: {}",
-            self.config.model,
-            is_reasoning_model
-        );
+        let settings = self.chat_settings();
+        let is_reasoning_model = settings.is_reasoning;
+        log::info!("Model {} settings: {:?}", self.config.model, settings);
 
-        // Role selection based on reasoning model type:
-        // - GPT-5 models: Use "system" role (they support system messages)
-        // - O-series models (o1, o3, o4-mini): Use "developer" role
-        // - Non-reasoning models: Use "system" role
-        let model_name = &self.config.model;
-        let system_role = if is_reasoning_model
-            && (model_name.starts_with("o1")
-                || model_name.starts_with("o3")
-                || model_name.starts_with("o4-mini"))
-        {
-            "developer"
-        } else {
-            "system"
-        };
         let system_content = if is_alternatives_request {
             // For alternatives requests, use the system prompt directly with minimal formatting
             if is_reasoning_model {
@@ -292,7 +270,7 @@ impl TranslationProvider for OpenAITranslationService {
             "model": self.config.model,
             "messages": [
                 {
-                    "role": system_role,
+                    "role": settings.system_role,
                     "content": system_content
                 },
                 {
@@ -302,27 +280,49 @@ impl TranslationProvider for OpenAITranslationService {
             ],
         });
 
-        // Use appropriate token parameter based on model type
+        // Reasoning models only accept `max_completion_tokens`, which also covers reasoning tokens
         if is_reasoning_model {
-            request_body["max_completion_tokens"] = json!(800);
-            // Add reasoning object for reasoning models
-            let effort = self.config.reasoning_effort.as_deref().unwrap_or("low");
-            request_body["reasoning"] = json!({
-                "effort": effort
-            });
-            log::info!("OpenAI - Using reasoning effort: {}", effort);
-
-            // Add verbosity parameter for GPT-5 models
-            if self.config.model.starts_with("gpt-5") {
-                request_body["verbosity"] = json!("medium");
-                log::info!("OpenAI - Using verbosity: medium for GPT-5 model");
-            }
-
-            // Note: Reasoning models don't support temperature, top_p, presence_penalty, frequency_penalty, logprobs, top_logprobs, logit_bias parameters
+            request_body["max_completion_tokens"] = json!(settings.max_output_tokens);
         } else {
-            request_body["max_tokens"] = json!(800);
-            request_body["temperature"] = json!(0.3);
+            request_body["max_tokens"] = json!(settings.max_output_tokens);
+        }
+        if let Some(temperature) = settings.temperature {
+            request_body["temperature"] = json!(temperature);
         }
+        if let Some(effort) = &settings.reasoning_effort {
+            request_body["reasoning_effort"] = json!(effort);
+        }
+        if let Some(verbosity) = &settings.verbosity {
+            request_body["verbosity"] = json!(verbosity);
+        }
+
+        if self.structured_output {
+            request_body["response_format"] = json_schema_response_format(is_alternatives_request);
+        }
+
+        Ok(request_body)
+    }
+}
+
+#[async_trait]
+impl TranslationProvider for OpenAITranslationService {
+    fn capabilities(&self) -> ProviderCapabilities {
+        let settings = self.chat_settings();
+        ProviderCapabilities {
+            alternatives: true,
+            phrase_alternatives: true,
+            streaming: true,
+            language_detection: true,
+            max_input_chars: None,
+            max_chunk_chars: Some(chunking::chars_for_output_tokens(settings.reply_tokens())),
+            transliteration: true,
+            structured_output: self.structured_output,
+            reasoning_effort: settings.is_reasoning,
+        }
+    }
+
+    async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResult> {
+        let request_body = self.build_request_body(request)?;
 
         log::info!("Using OpenAI model: {}", self.config.model);
 
@@ -343,6 +343,11 @@ impl TranslationProvider for OpenAITranslationService {
             ));
         }
 
+        if choices[0]["finish_reason"].as_str() == Some("length") {
+            log::warn!("OpenAI response was cut off at max_tokens");
+            return Err(truncated_reply_error());
+        }
+
         let message = &choices[0]["message"];
         if message.is_null() {
             return Err(anyhow::anyhow!(
@@ -358,9 +363,7 @@ impl TranslationProvider for OpenAITranslationService {
             )
         })?;
 
-        // Check if this is an alternatives request
-        let is_alternatives_request = self.config.custom_prompt.contains("alternatives");
-        if is_alternatives_request {
+        if request.is_alternatives() {
             // For alternatives requests, return the raw content as translated_text
             // The calling code will parse the JSON alternatives from it
             log::info!("Returning raw alternatives response");
@@ -368,23 +371,33 @@ impl TranslationProvider for OpenAITranslationService {
                 detected_language: "unknown".to_string(),
                 translated_text: content.to_string(),
                 target_language: "alternatives".to_string(),
+                transliteration: None,
             })
         } else {
-            self.parse_response_content(content)
+            log::info!("API Response content: {}", content);
+            Ok(parse_translation_json(content, &request.target_language))
         }
     }
-}
 
-fn is_reasoning_model_name(name: &str) -> bool {
-    let n = name.to_lowercase();
-    // Known OpenAI reasoning model families and variants based on Microsoft documentation
-    // GPT-5 series: gpt-5, gpt-5-mini, gpt-5-nano, gpt-5-chat, gpt-5-thinking
-    n.starts_with("gpt-5") ||
-    n.starts_with("gpt5") ||
-    // O-series models: o1, o1-mini, o3, o3-mini, o3-pro, o4-mini
-    n.starts_with("o1") ||
-    n.starts_with("o3") ||
-    n.starts_with("o4-mini") ||
-    // Codex reasoning model
-    n.starts_with("codex-mini")
+    async fn translate_streaming(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: ChunkSink,
+    ) -> Result<TranslationResult> {
+        // Alternatives are parsed as a whole by the caller, so there is nothing to stream
+        if request.is_alternatives() {
+            return self.translate(request).await;
+        }
+
+        let request_body = self.build_request_body(request)?;
+        log::info!("Streaming with OpenAI model: {}", self.config.model);
+
+        let response = self.call_openai_stream(request_body).await?;
+        let content = read_chat_completion_stream(response, &on_chunk).await?;
+        if content.is_empty() {
+            return Err(anyhow::anyhow!("Empty streamed response from OpenAI"));
+        }
+
+        Ok(parse_translation_json(&content, &request.target_language))
+    }
 }
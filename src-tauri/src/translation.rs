@@ -1,31 +1,175 @@
+use crate::chunking::{self, TextChunk};
 use crate::config::Config;
-use crate::provider_factory::create_provider;
-use crate::trans_azure::AzureOpenAITranslationService;
-use crate::trans_ollama::OllamaTranslationService;
-use crate::trans_openai::OpenAITranslationService;
+use crate::failover;
+use crate::languages;
+use crate::provider_factory::{
+    create_alternatives_provider, create_provider, uses_alternatives_fallback,
+};
 use anyhow::Result;
 use async_trait::async_trait;
+use futures_util::StreamExt;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use whatlang::{Lang, detect};
+use tokio_util::sync::CancellationToken;
+use whatlang::detect;
 
 lazy_static! {
-    static ref IN_FLIGHT_REQUESTS: Arc<Mutex<HashMap<String, std::time::Instant>>> =
-        Arc::new(Mutex::new(HashMap::new()));
+    // The translation currently running in each window (by label), so a newer one can supersede it
+    static ref ACTIVE_TRANSLATIONS: Mutex<HashMap<String, ActiveTranslation>> =
+        Mutex::new(HashMap::new());
+}
+
+static NEXT_TRANSLATION_ID: AtomicU64 = AtomicU64::new(0);
+
+// Chunks of a long text translated at the same time, low enough to stay clear of rate limits
+const MAX_CONCURRENT_CHUNKS: usize = 4;
+
+// Length of the document opening shared with every chunk as context
+const CHUNK_CONTEXT_CHARS: usize = 300;
+
+struct ActiveTranslation {
+    id: u64,
+    request_hash: u64,
+    token: CancellationToken,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranslationResult {
-    pub detected_language: String,
+    pub detected_language: String, // Language tag, or "unknown"
+    pub translated_text: String,
+    pub target_language: String, // Language tag
+    #[serde(default)]
+    pub transliteration: Option<String>, // Latin-script romanization, when enabled for the target language
+}
+
+/// What a provider supports, so callers (and the settings UI) can skip unsupported features
+/// up front instead of failing at runtime.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    /// Can answer `TranslationOperation::Alternatives` requests.
+    pub alternatives: bool,
+    /// Alternatives also work for multi-word phrases, not just single words.
+    pub phrase_alternatives: bool,
+    /// `translate_streaming` reports partial output.
+    pub streaming: bool,
+    /// Detects the source language itself.
+    pub language_detection: bool,
+    /// Longest text accepted in one request, above which texts are split; `None` when only the
+    /// model's context limits it.
+    pub max_input_chars: Option<usize>,
+    /// Longest text whose translation fits in the model's output limit. Longer texts are
+    /// translated in chunks of this size.
+    pub max_chunk_chars: Option<usize>,
+    /// Adds a romanization of translations into non-Latin scripts.
+    pub transliteration: bool,
+    /// Replies are constrained to the reply JSON schema.
+    pub structured_output: bool,
+    /// Honours `Config::reasoning_effort`.
+    pub reasoning_effort: bool,
+}
+
+/// What a provider is asked to do with the request text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationOperation {
+    /// Translate the text, detecting its language unless a source override is set.
+    Translate,
+    /// Suggest other renderings of the text in the target language. Providers return the raw
+    /// `{"alternatives": [...]}` JSON as `translated_text`.
+    Alternatives,
+}
+
+/// A single unit of work for a [`TranslationProvider`].
+#[derive(Debug, Clone)]
+pub struct TranslationRequest {
+    pub operation: TranslationOperation,
+    pub text: String,
+    pub source_language: Option<String>, // Manual source language override (None = auto-detect)
+    pub target_language: String,         // Language tag, like all languages of a request
+    pub alternative_target_language: String, // Used when the text already is in the target language
+    pub context: Option<String>, // Surrounding text that helps to disambiguate; for a chunk, the document's opening
+    pub transliteration_languages: Vec<String>, // Target languages whose translation gets a romanization
+}
+
+impl TranslationRequest {
+    /// A translation into the languages configured in `config`.
+    pub fn translate(text: impl Into<String>, config: &Config) -> Self {
+        Self {
+            operation: TranslationOperation::Translate,
+            text: text.into(),
+            source_language: config.user_source_language.clone(),
+            target_language: config.target_language.clone(),
+            alternative_target_language: config.alternative_target_language.clone(),
+            context: None,
+            transliteration_languages: config.transliteration_languages.clone(),
+        }
+    }
+
+    /// Alternative renderings of `text`, which is already in `target_language`.
+    pub fn alternatives(
+        text: impl Into<String>,
+        target_language: impl Into<String>,
+        context: Option<String>,
+    ) -> Self {
+        let target_language = target_language.into();
+        Self {
+            operation: TranslationOperation::Alternatives,
+            text: text.into(),
+            source_language: Some(target_language.clone()),
+            alternative_target_language: target_language.clone(),
+            target_language,
+            context,
+            transliteration_languages: Vec::new(),
+        }
+    }
+
+    pub fn is_alternatives(&self) -> bool {
+        self.operation == TranslationOperation::Alternatives
+    }
+
+    pub fn wants_transliteration(&self, language: &str) -> bool {
+        self.transliteration_languages
+            .iter()
+            .any(|l| languages::same_language(l, language))
+    }
+}
+
+/// Partial translation emitted to the frontend as `translation-chunk` while a provider streams.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationChunk {
     pub translated_text: String,
-    pub target_language: String,
 }
 
+/// Callback that receives the partial translated text produced so far.
+pub type ChunkSink = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Progress of a long text translated in chunks, emitted to the frontend as `translation-progress`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationProgress {
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Callback that receives the progress of a chunked translation.
+pub type ProgressSink = Arc<dyn Fn(TranslationProgress) + Send + Sync>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlternativeTranslationsResult {
     pub alternatives: Vec<String>,
+    #[serde(default)]
+    pub details: Vec<AlternativeDetail>, // Only filled in by dictionary based providers
+}
+
+/// Dictionary information about one alternative.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlternativeDetail {
+    pub text: String,
+    pub part_of_speech: Option<String>,
+    pub example: Option<String>, // Usage example in the target language
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,11 +178,15 @@ pub struct TranslationResponse {
     pub translated_text: String,
     pub detected_language: String,
     pub target_language: String,
+    pub transliteration: Option<String>,
+    pub provider: String, // The provider that served the request, which may be a failover one
+    pub model: String,
 }
 
 #[derive(Debug)]
 pub enum Error {
     DuplicateRequest,
+    Cancelled,
     ApiError(anyhow::Error),
 }
 
@@ -46,6 +194,7 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::DuplicateRequest => write!(f, "Duplicate request"),
+            Error::Cancelled => write!(f, "Translation cancelled"),
             Error::ApiError(e) => write!(f, "API error: {}", e),
         }
     }
@@ -59,9 +208,63 @@ impl From<anyhow::Error> for Error {
     }
 }
 
+/// A failed provider request with what went wrong, so callers such as the failover chain can
+/// classify it by downcasting instead of reading the message.
+#[derive(Debug)]
+pub struct ProviderError {
+    pub kind: ProviderErrorKind,
+    message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderErrorKind {
+    Status(reqwest::StatusCode), // The API answered with an error status
+    NotConfigured,               // A model, URL or key the request needs is not set
+    Blocked,                     // A content filter refused the text
+}
+
+impl ProviderError {
+    pub fn status(status: reqwest::StatusCode, message: String) -> anyhow::Error {
+        Self::new(ProviderErrorKind::Status(status), message)
+    }
+
+    pub fn not_configured(message: &str) -> anyhow::Error {
+        Self::new(ProviderErrorKind::NotConfigured, message.to_string())
+    }
+
+    pub fn blocked(message: String) -> anyhow::Error {
+        Self::new(ProviderErrorKind::Blocked, message)
+    }
+
+    fn new(kind: ProviderErrorKind, message: String) -> anyhow::Error {
+        anyhow::Error::new(Self { kind, message })
+    }
+}
+
+impl std::fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
 #[async_trait]
 pub trait TranslationProvider {
-    async fn translate(&self, text: &str) -> Result<TranslationResult>;
+    fn capabilities(&self) -> ProviderCapabilities;
+
+    async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResult>;
+
+    /// Same as `translate`, but reports partial output to `on_chunk` as it arrives.
+    /// Providers without a streaming API fall back to a single blocking request.
+    async fn translate_streaming(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: ChunkSink,
+    ) -> Result<TranslationResult> {
+        let _ = on_chunk;
+        self.translate(request).await
+    }
 }
 
 pub struct TranslationService {
@@ -75,77 +278,242 @@ impl TranslationService {
         }
     }
 
-    pub async fn detect_and_translate(&self, text: &str) -> Result<TranslationResult> {
-        // Create a more unique request key that includes current timestamp to prevent issues
-        // with legitimate duplicate requests (e.g., user retrying the same text)
-        let request_key = format!(
-            "{}-{}",
-            text.len(),
-            text.chars().take(50).collect::<String>()
-        );
+    pub fn capabilities(&self) -> ProviderCapabilities {
+        self.provider.capabilities()
+    }
 
-        let now = std::time::Instant::now();
+    pub async fn detect_and_translate(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: Option<ChunkSink>,
+        on_progress: Option<ProgressSink>,
+    ) -> Result<TranslationResult> {
+        let capabilities = self.provider.capabilities();
+        // A romanization roughly doubles the reply, so it halves what fits in one request
+        let chunk_chars = capabilities
+            .max_chunk_chars
+            .map(|chars| {
+                if request.transliteration_languages.is_empty() {
+                    chars
+                } else {
+                    chars / 2
+                }
+            })
+            .or(capabilities.max_input_chars);
+        let chunks = match chunk_chars {
+            Some(max_chars)
+                if !request.is_alternatives() && request.text.chars().count() > max_chars =>
+            {
+                chunking::split_text(&request.text, max_chars)
+            }
+            _ => Vec::new(),
+        };
 
-        {
-            let mut requests = IN_FLIGHT_REQUESTS.lock().unwrap();
+        let mut result = if chunks.len() > 1 {
+            self.translate_chunks(request, chunks, on_chunk, on_progress)
+                .await?
+        } else {
+            match on_chunk {
+                Some(on_chunk) => self.provider.translate_streaming(request, on_chunk).await?,
+                None => self.provider.translate(request).await?,
+            }
+        };
 
-            // Clean up old requests (older than 5 seconds)
-            requests.retain(|_, &mut timestamp| now.duration_since(timestamp).as_secs() < 5);
+        // Models sometimes romanize anyway; only keep it where the user asked for it
+        if !request.wants_transliteration(&result.target_language) {
+            result.transliteration = None;
+        }
+        Ok(result)
+    }
 
-            // Check if there's a recent request for the same content
-            if let Some(&request_time) = requests.get(&request_key) {
-                let time_diff = now.duration_since(request_time);
-                // Only consider it a duplicate if it's within 500ms
-                if time_diff.as_millis() < 500 {
-                    log::info!(
-                        "Duplicate translation request detected within 500ms, skipping API call"
-                    );
-                    return Err(anyhow::anyhow!("Duplicate request detected"));
-                }
+    /// Translates the chunks of a long text concurrently and joins the translations in the
+    /// original order. Every chunk carries the opening of the document as shared context.
+    async fn translate_chunks(
+        &self,
+        request: &TranslationRequest,
+        chunks: Vec<TextChunk>,
+        on_chunk: Option<ChunkSink>,
+        on_progress: Option<ProgressSink>,
+    ) -> Result<TranslationResult> {
+        let total = chunks.len();
+        log::info!(
+            "Translating {} characters in {} chunks",
+            request.text.chars().count(),
+            total
+        );
+
+        // The target was chosen for the whole text; a chunk that happens to be in the target
+        // language must not switch to the alternative one
+        let context = chunk_context(&request.text);
+        let requests: Vec<TranslationRequest> = chunks
+            .iter()
+            .map(|chunk| TranslationRequest {
+                text: chunk.text.clone(),
+                alternative_target_language: request.target_language.clone(),
+                context: Some(context.clone()),
+                ..request.clone()
+            })
+            .collect();
+
+        let report = |completed| {
+            if let Some(on_progress) = &on_progress {
+                on_progress(TranslationProgress { completed, total });
             }
+        };
+        report(0);
 
-            requests.insert(request_key.clone(), now);
+        let mut pending = futures_util::stream::iter(requests.iter().enumerate())
+            .map(|(index, chunk_request)| async move {
+                (index, self.provider.translate(chunk_request).await)
+            })
+            .buffer_unordered(MAX_CONCURRENT_CHUNKS);
+
+        let mut results: Vec<Option<TranslationResult>> = vec![None; total];
+        let mut completed = 0;
+        let mut emitted = 0;
+        while let Some((index, result)) = pending.next().await {
+            results[index] = Some(result?);
+            completed += 1;
+            report(completed);
+
+            // Show the translation up to the first chunk that is still missing
+            let ready = results.iter().take_while(|r| r.is_some()).count();
+            if let Some(on_chunk) = &on_chunk
+                && ready > emitted
+            {
+                let partial = join_chunks(&chunks[..ready], &results[..ready], |r| {
+                    Some(&r.translated_text)
+                });
+                on_chunk(partial.unwrap_or_default().trim_end());
+                emitted = ready;
+            }
         }
 
-        let result = self.provider.translate(text).await;
+        let translated_text = join_chunks(&chunks, &results, |r| Some(&r.translated_text))
+            .unwrap_or_default()
+            .trim_end()
+            .to_string();
+        // Only a complete romanization is worth showing
+        let transliteration = join_chunks(&chunks, &results, |r| r.transliteration.as_ref())
+            .map(|text| text.trim_end().to_string());
+        let first = results
+            .into_iter()
+            .flatten()
+            .find(|r| !r.detected_language.eq_ignore_ascii_case("unknown"));
+
+        Ok(TranslationResult {
+            detected_language: first
+                .as_ref()
+                .map_or_else(|| "unknown".to_string(), |r| r.detected_language.clone()),
+            translated_text,
+            target_language: first
+                .map_or_else(|| request.target_language.clone(), |r| r.target_language),
+            transliteration,
+        })
+    }
+}
 
-        {
-            let mut requests = IN_FLIGHT_REQUESTS.lock().unwrap();
-            requests.remove(&request_key);
+/// Joins translated chunks with the separators of the source chunks. `None` when a chunk has
+/// no translation or `text` yields nothing for one.
+fn join_chunks(
+    chunks: &[TextChunk],
+    results: &[Option<TranslationResult>],
+    text: impl Fn(&TranslationResult) -> Option<&String>,
+) -> Option<String> {
+    let mut joined = String::new();
+    for (chunk, result) in chunks.iter().zip(results) {
+        joined.push_str(text(result.as_ref()?)?.trim());
+        joined.push_str(&chunk.separator);
+    }
+    Some(joined)
+}
+
+/// The opening of a document, cut at a word boundary, which every chunk gets as context.
+fn chunk_context(text: &str) -> String {
+    if text.chars().count() <= CHUNK_CONTEXT_CHARS {
+        return text.trim().to_string();
+    }
+    let opening: String = text.chars().take(CHUNK_CONTEXT_CHARS).collect();
+    let opening = opening
+        .rsplit_once(char::is_whitespace)
+        .map_or(opening.as_str(), |(words, _)| words);
+    format!("{}…", opening.trim())
+}
+
+/// Error for a reply that stopped at the output token limit, which would otherwise pass as a
+/// complete translation.
+pub fn truncated_reply_error() -> anyhow::Error {
+    anyhow::anyhow!(
+        "The translation was cut off at the model's output token limit. Raise the model's max output tokens under Settings > Models."
+    )
+}
+
+/// Identifies a translation by everything that influences its result.
+fn request_hash(request: &TranslationRequest, config: &Config) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    request.text.hash(&mut hasher);
+    request.source_language.hash(&mut hasher);
+    request.target_language.hash(&mut hasher);
+    request.alternative_target_language.hash(&mut hasher);
+    request.transliteration_languages.hash(&mut hasher);
+    config.api_provider.hash(&mut hasher);
+    config.model.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Registration of the translation running in a window. Dropping it unregisters the
+/// translation again, unless a newer one has taken its place in the meantime.
+struct TranslationGuard {
+    window: String,
+    id: u64,
+    token: CancellationToken,
+}
+
+impl TranslationGuard {
+    /// Registers a translation for `window` and cancels the one it supersedes. Returns `None`
+    /// when an identical translation is already running in that window.
+    fn begin(window: &str, request_hash: u64) -> Option<Self> {
+        let mut active = ACTIVE_TRANSLATIONS.lock().unwrap();
+        if let Some(previous) = active.get(window) {
+            if previous.request_hash == request_hash && !previous.token.is_cancelled() {
+                return None;
+            }
+            previous.token.cancel();
         }
 
-        result
+        let id = NEXT_TRANSLATION_ID.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        active.insert(
+            window.to_string(),
+            ActiveTranslation {
+                id,
+                request_hash,
+                token: token.clone(),
+            },
+        );
+        Some(Self {
+            window: window.to_string(),
+            id,
+            token,
+        })
     }
 }
 
-fn map_whatlang_lang_to_name(lang: Lang) -> String {
-    use Lang::*;
-    let name = match lang {
-        Eng => "English",
-        Spa => "Spanish",
-        Fra => "French",
-        Deu => "German",
-        Ita => "Italian",
-        Por => "Portuguese",
-        Rus => "Russian",
-        Jpn => "Japanese",
-        Kor => "Korean",
-        Cmn => "Chinese",
-        Ara => "Arabic",
-        Hin => "Hindi",
-        Nld => "Dutch",
-        Swe => "Swedish",
-        Nob => "Norwegian",
-        Dan => "Danish",
-        Fin => "Finnish",
-        Pol => "Polish",
-        Tur => "Turkish",
-        Ces => "Czech",
-        Hun => "Hungarian",
-        // Languages not explicitly mapped above fall back to debug code
-        other => return format!("{:?}", other),
-    };
-    name.to_string()
+impl Drop for TranslationGuard {
+    fn drop(&mut self) {
+        if let Ok(mut active) = ACTIVE_TRANSLATIONS.lock()
+            && active.get(&self.window).is_some_and(|a| a.id == self.id)
+        {
+            active.remove(&self.window);
+        }
+    }
+}
+
+/// Aborts the translation running in `window`. Called by the frontend when the input text changes.
+pub fn cancel_translation(window: &str) {
+    if let Some(active) = ACTIVE_TRANSLATIONS.lock().unwrap().get(window) {
+        active.token.cancel();
+    }
 }
 
 pub fn clean_text_for_translation(text: &str) -> String {
@@ -157,53 +525,250 @@ pub fn clean_text_for_translation(text: &str) -> String {
         .join("\n")
 }
 
-pub fn create_smart_prompt(config: &Config, _source_override: Option<&str>) -> String {
+/// JSON schema of a translation reply: `{detected_language, translated_text}`.
+pub fn translation_json_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "detected_language": { "type": "string" },
+            "translated_text": { "type": "string" },
+            "transliteration": { "type": ["string", "null"] }
+        },
+        "required": ["detected_language", "translated_text", "transliteration"],
+        "additionalProperties": false
+    })
+}
+
+/// JSON schema of an alternatives reply: `{alternatives: [...]}`.
+pub fn alternatives_json_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "alternatives": {
+                "type": "array",
+                "items": { "type": "string" }
+            }
+        },
+        "required": ["alternatives"],
+        "additionalProperties": false
+    })
+}
+
+/// `response_format` for chat completion APIs (OpenAI, Azure OpenAI) that makes the model
+/// follow the reply schema instead of merely being asked to produce JSON.
+pub fn json_schema_response_format(is_alternatives_request: bool) -> serde_json::Value {
+    let (name, schema) = if is_alternatives_request {
+        ("alternatives", alternatives_json_schema())
+    } else {
+        ("translation", translation_json_schema())
+    };
+
+    serde_json::json!({
+        "type": "json_schema",
+        "json_schema": {
+            "name": name,
+            "strict": true,
+            "schema": schema
+        }
+    })
+}
+
+/// Parser for LLM output that should contain `{detected_language, translated_text}`.
+/// Replies constrained by [`translation_json_schema`] parse directly; for providers without
+/// structured output it tolerates control characters, JSON wrapped in prose or code fences,
+/// and plain-text replies.
+pub fn parse_translation_json(content: &str, target_language: &str) -> TranslationResult {
+    // Clean the content by removing control characters that can break JSON parsing
+    let cleaned_content = content
+        .chars()
+        .filter(|c| !c.is_control() || matches!(*c, '\n' | '\r' | '\t'))
+        .collect::<String>();
+
+    if cleaned_content != content {
+        log::warn!("Removed control characters from API response");
+    }
+
+    let parsed: serde_json::Value = match serde_json::from_str(&cleaned_content) {
+        Ok(json) => json,
+        Err(parse_error) => {
+            log::warn!("Failed to parse as JSON: {}", parse_error);
+            extract_json_object(&cleaned_content).unwrap_or_else(|| {
+                log::warn!("No usable JSON structure found in response");
+                serde_json::json!({
+                    "detected_language": "unknown",
+                    "translated_text": cleaned_content
+                })
+            })
+        }
+    };
+
+    let detected_language = match parsed["detected_language"].as_str() {
+        // Models report a language name; keep the tag it stands for
+        Some(lang) if !lang.is_empty() && !lang.eq_ignore_ascii_case("unknown") => {
+            languages::tag(lang)
+        }
+        _ => "unknown".to_string(),
+    };
+    let translated_text = match parsed["translated_text"].as_str().or(parsed.as_str()) {
+        Some(text) => text
+            .replace("\\r\\n", "\n")
+            .replace("\\n", "\n")
+            .replace("\\r", "\n")
+            .replace("\\t", "\t"),
+        None => "translation failed".to_string(),
+    };
+    let transliteration = parsed["transliteration"]
+        .as_str()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string);
+
+    log::info!(
+        "Detected language (provider reported): {}",
+        detected_language
+    );
+    log::info!(
+        "Translated text (first 100 chars): {}",
+        translated_text.chars().take(100).collect::<String>()
+    );
+
+    TranslationResult {
+        detected_language,
+        translated_text,
+        target_language: target_language.to_string(),
+        transliteration,
+    }
+}
+
+/// Finds the first balanced `{...}` block in `content` and parses it as JSON.
+fn extract_json_object(content: &str) -> Option<serde_json::Value> {
+    let start_idx = content.find('{')?;
+    let mut brace_count = 0;
+
+    for (i, c) in content[start_idx..].char_indices() {
+        if c == '{' {
+            brace_count += 1;
+        } else if c == '}' {
+            brace_count -= 1;
+            if brace_count == 0 {
+                let json_str = &content[start_idx..start_idx + i + 1];
+                return match serde_json::from_str(json_str) {
+                    Ok(json) => Some(json),
+                    Err(e) => {
+                        log::warn!("Failed to parse extracted JSON: {}", e);
+                        None
+                    }
+                };
+            }
+        }
+    }
+
+    None
+}
+
+/// System prompt for a translation: the user's prompt plus the smart target switching rules.
+pub fn create_smart_prompt(config: &Config, request: &TranslationRequest) -> String {
+    let target = languages::english_name(&request.target_language);
+    let alternative = languages::english_name(&request.alternative_target_language);
     let prompt = format!(
-        "{}\n\n# Translation Rules\n- Primary target language: {}\n- Alternative target language: {}\n\n**IMPORTANT**: \n- If the detected source language is the same as the primary target language ({}), then translate to the alternative target language ({}) instead.\n- If the detected source language is different from the primary target language ({}), then translate to the primary target language ({}).\n\nExample:\n- If text is in {} and primary target is {}, translate to {}\n- If text is in any other language and primary target is {}, translate to {}",
+        "{}\n\n# Translation Rules\n- Primary target language: {}\n- Alternative target language: {}\n\n**IMPORTANT**: \n- If the detected source language is the same as the primary target language ({}), then translate to the alternative target language ({}) instead. Regional and script variants of a language count as the same language.\n- If the detected source language is different from the primary target language ({}), then translate to the primary target language ({}).\n\nExample:\n- If text is in {} and primary target is {}, translate to {}\n- If text is in any other language and primary target is {}, translate to {}",
         config.custom_prompt,
-        config.target_language,
-        config.alternative_target_language,
-        config.target_language,
-        config.alternative_target_language,
-        config.target_language,
-        config.target_language,
-        config.target_language,
-        config.target_language,
-        config.alternative_target_language,
-        config.target_language,
-        config.target_language
+        target,
+        alternative,
+        target,
+        alternative,
+        target,
+        target,
+        target,
+        target,
+        alternative,
+        target,
+        target
     );
 
+    // Name the variant's locale explicitly; a bare "Portuguese" gets whichever variant the
+    // model prefers
+    let conventions = [
+        &request.target_language,
+        &request.alternative_target_language,
+    ]
+    .into_iter()
+    .filter_map(|tag| {
+        languages::conventions(tag).map(|conventions| {
+            format!(
+                "- {} ({}): use {}",
+                languages::english_name(tag),
+                tag,
+                conventions
+            )
+        })
+    })
+    .collect::<Vec<_>>();
+    let prompt = if conventions.is_empty() {
+        prompt
+    } else {
+        format!(
+            "{}\n\n# Locale Conventions\n{}",
+            prompt,
+            conventions.join("\n")
+        )
+    };
+
+    // Chunks of a long text share the document opening, so terms stay consistent across them
+    let prompt = match request.context.as_deref() {
+        Some(context) if !context.trim().is_empty() => format!(
+            "{}\n\n# Document Context\n- The text is one part of a longer document that is translated in parts. Translate only this part, completely, and keep names and terminology consistent with the rest of the document.\n- The document begins: \"{}\"",
+            prompt, context
+        ),
+        _ => prompt,
+    };
+
+    let prompt = if request.transliteration_languages.is_empty() {
+        prompt
+    } else {
+        format!(
+            "{}\n\n# Transliteration\n- If you translate into one of these languages: {}, also return a 'transliteration' field with a romanization of the translation in Latin script (Hepburn romaji for Japanese, Hanyu Pinyin with tone marks for Chinese, scientific transliteration for Cyrillic, IAST for Devanagari).\n- Otherwise set 'transliteration' to null.",
+            prompt,
+            request
+                .transliteration_languages
+                .iter()
+                .map(|tag| languages::english_name(tag))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+
     log::info!("Generated smart prompt: {}", prompt);
     prompt
 }
 
-pub async fn translate_text(
-    text: String,
-    config: tauri::State<'_, crate::AppState>,
-) -> Result<TranslationResponse, Error> {
-    log::info!("translate_text called with text: {}", text);
-
-    let config_guard = config.config.lock().await;
-    let mut config_clone = config_guard.clone();
-    drop(config_guard);
-
-    log::info!(
-        "Config loaded - API Provider: {}, Model: {}, Target: {}, Alternative: {}, Custom Prompt: {}",
-        config_clone.api_provider,
-        config_clone.model,
-        config_clone.target_language,
-        config_clone.alternative_target_language,
-        config_clone.custom_prompt
-    );
+/// System prompt for an alternatives request.
+pub fn create_alternatives_prompt(request: &TranslationRequest) -> String {
+    let context = request
+        .context
+        .as_deref()
+        .filter(|context| !context.trim().is_empty())
+        .map(|context| format!("\n\nThe text is part of this passage: \"{}\"", context))
+        .unwrap_or_default();
+
+    format!(
+        "Provide up to 5 different word choices or synonyms for the following text when translated into {}. Return ONLY the alternative words/phrases as a JSON array under the key 'alternatives'. Do not include the original word. If no alternatives exist, return an empty array. Do not include any other text or explanation.\n\nText: \"{}\"{}\n\nRespond in JSON format like this:\n{{\"alternatives\": [\"Alternative 1\", \"Alternative 2\", \"Alternative 3\"]}}",
+        languages::english_name(&request.target_language),
+        request.text,
+        context
+    )
+}
 
-    // --- Pre-detect language to choose effective target (smart switch) ---
-    let primary = config_clone.target_language.clone();
-    let alternative = config_clone.alternative_target_language.clone();
+/// Pre-detects the source language (unless the user set one) and applies the smart switch:
+/// text already in the primary target language goes to the alternative target instead.
+/// Returns the pre-detected language, if any, and the effective target language.
+pub fn choose_target_language(text: &str, config: &Config) -> (Option<String>, String) {
+    let primary = config.target_language.clone();
+    let alternative = config.alternative_target_language.clone();
     let mut pre_detected_language: Option<String> = None;
 
     // Respect explicit user source override: treat it as detected.
-    if let Some(user_source) = config_clone.user_source_language.clone() {
+    if let Some(user_source) = config.user_source_language.clone() {
         log::info!("User source language override present: {}", user_source);
         pre_detected_language = Some(user_source);
     } else {
@@ -211,15 +776,16 @@ pub async fn translate_text(
         if trimmed.chars().count() >= 3 {
             // avoid unreliable detection on ultra-short text
             if let Some(info) = detect(trimmed) {
-                let lang_name = map_whatlang_lang_to_name(info.lang());
+                let lang_tag = languages::from_whatlang(info.lang())
+                    .unwrap_or_else(|| info.lang().eng_name().to_string());
                 log::info!(
                     "Pre-detected language via whatlang: {} (confidence {:.2})",
-                    lang_name,
+                    lang_tag,
                     info.confidence()
                 );
                 if info.confidence() >= 0.70 {
                     // confidence threshold
-                    pre_detected_language = Some(lang_name);
+                    pre_detected_language = Some(lang_tag);
                 } else {
                     log::info!(
                         "Detection confidence below threshold ({:.2}), ignoring",
@@ -236,15 +802,16 @@ pub async fn translate_text(
 
     // Decide effective target
     let effective_target = match pre_detected_language.as_ref() {
-        Some(detected) if detected.eq_ignore_ascii_case(&primary) => {
+        // Any variant of the primary target counts: Portuguese text with a pt-BR primary switches
+        Some(detected) if languages::same_base_language(detected, &primary) => {
             // Check if primary and alternative languages are the same (misconfiguration)
-            if primary.eq_ignore_ascii_case(&alternative) {
+            if languages::same_language(&primary, &alternative) {
                 log::warn!(
                     "Configuration issue: primary ('{}') and alternative ('{}') target languages are the same. Using Spanish as fallback.",
                     primary,
                     alternative
                 );
-                "Spanish".to_string()
+                "es".to_string()
             } else {
                 log::info!(
                     "Smart switch engaged: detected '{}' == primary '{}'; using alternative '{}'",
@@ -280,50 +847,143 @@ pub async fn translate_text(
         effective_target
     );
 
-    // Mutate only the local clone's target_language so provider translates directly to chosen language.
-    config_clone.target_language = effective_target.clone();
+    (pre_detected_language, effective_target)
+}
 
-    let service = TranslationService::new(config_clone.clone());
-    match service.detect_and_translate(&text).await {
-        Ok(result) => {
-            log::info!("Translation completed successfully");
-            log::info!("Detected language: {}", result.detected_language);
-            log::info!(
-                "Translated text length: {} characters",
-                result.translated_text.len()
-            );
+pub async fn translate_text(
+    text: String,
+    window: &str,
+    config: tauri::State<'_, crate::AppState>,
+    on_chunk: Option<ChunkSink>,
+    on_progress: Option<ProgressSink>,
+) -> Result<TranslationResponse, Error> {
+    log::info!("translate_text called with text: {}", text);
 
-            // Prefer provider's detected language unless unknown, then fallback to pre-detected
-            let final_detected = if result.detected_language.eq_ignore_ascii_case("unknown") {
-                pre_detected_language
-                    .clone()
-                    .unwrap_or_else(|| "unknown".to_string())
-            } else {
-                result.detected_language.clone()
-            };
+    let config_guard = config.config.lock().await;
+    let config_clone = config_guard.clone();
+    drop(config_guard);
 
-            Ok(TranslationResponse {
-                original_text: text,
-                translated_text: result.translated_text,
-                detected_language: final_detected,
-                target_language: effective_target,
-            })
+    log::info!(
+        "Config loaded - API Provider: {}, Model: {}, Target: {}, Alternative: {}, Custom Prompt: {}",
+        config_clone.api_provider,
+        config_clone.model,
+        config_clone.target_language,
+        config_clone.alternative_target_language,
+        config_clone.custom_prompt
+    );
+
+    // --- Pre-detect language to choose effective target (smart switch) ---
+    let (pre_detected_language, effective_target) = choose_target_language(&text, &config_clone);
+
+    // The provider translates directly into the chosen language
+    let mut request = TranslationRequest::translate(text.clone(), &config_clone);
+    request.target_language = effective_target.clone();
+
+    // A newer translation in the same window cancels this one; an identical one is not repeated
+    let Some(guard) = TranslationGuard::begin(window, request_hash(&request, &config_clone)) else {
+        log::info!("Identical translation already running, skipping duplicate request");
+        return Err(Error::DuplicateRequest);
+    };
+
+    // Try the active provider first, then the failover chain on errors another provider may avoid
+    let mut last_error = None;
+    for candidate in failover::provider_chain(&config_clone) {
+        let service = TranslationService::new(candidate.clone());
+
+        // Only stream when enabled and supported; otherwise wait for the complete response.
+        // Superseded translations must not paint their partial output over the newer one.
+        let on_chunk = on_chunk
+            .clone()
+            .filter(|_| candidate.stream_translations && service.capabilities().streaming)
+            .map(|on_chunk| {
+                let token = guard.token.clone();
+                Arc::new(move |partial: &str| {
+                    if !token.is_cancelled() {
+                        on_chunk(partial);
+                    }
+                }) as ChunkSink
+            });
+        let on_progress = on_progress.clone().map(|on_progress| {
+            let token = guard.token.clone();
+            Arc::new(move |progress: TranslationProgress| {
+                if !token.is_cancelled() {
+                    on_progress(progress);
+                }
+            }) as ProgressSink
+        });
+
+        // Dropping the provider future also drops any open stream, so the backend stops generating
+        let result = tokio::select! {
+            result = service.detect_and_translate(&request, on_chunk, on_progress) => result,
+            _ = guard.token.cancelled() => Err(anyhow::anyhow!("Translation cancelled")),
+        };
+
+        // The provider may have finished just as a newer translation superseded this one
+        if guard.token.is_cancelled() {
+            log::info!("Translation cancelled by a newer request");
+            return Err(Error::Cancelled);
         }
-        Err(e) => {
-            log::error!("Translation failed: {}", e);
-            if e.to_string().contains("Duplicate request detected") {
-                Err(Error::DuplicateRequest)
-            } else {
-                Err(Error::ApiError(e))
+
+        match result {
+            Ok(result) => {
+                failover::record_success(&candidate);
+                log::info!(
+                    "Translation completed successfully with {} ({})",
+                    candidate.api_provider,
+                    candidate.model
+                );
+                log::info!("Detected language: {}", result.detected_language);
+                log::info!(
+                    "Translated text length: {} characters",
+                    result.translated_text.len()
+                );
+
+                // Prefer provider's detected language unless unknown, then fallback to pre-detected
+                let final_detected = if result.detected_language.eq_ignore_ascii_case("unknown") {
+                    pre_detected_language
+                        .clone()
+                        .unwrap_or_else(|| "unknown".to_string())
+                } else {
+                    result.detected_language.clone()
+                };
+
+                return Ok(TranslationResponse {
+                    original_text: text,
+                    translated_text: result.translated_text,
+                    detected_language: final_detected,
+                    target_language: effective_target,
+                    transliteration: result.transliteration,
+                    provider: candidate.api_provider,
+                    model: candidate.model,
+                });
+            }
+            Err(e) if failover::should_fail_over(&e) => {
+                failover::record_failure(&candidate);
+                log::warn!(
+                    "Translation with {} ({}) failed, trying the next provider: {}",
+                    candidate.api_provider,
+                    candidate.model,
+                    e
+                );
+                last_error = Some(e);
+            }
+            Err(e) => {
+                log::error!("Translation failed: {}", e);
+                return Err(Error::ApiError(e));
             }
         }
     }
+
+    let e = last_error.unwrap_or_else(|| anyhow::anyhow!("No translation provider configured"));
+    log::error!("Translation failed with every provider: {}", e);
+    Err(Error::ApiError(e))
 }
 
 /// Generate alternative translations for a given text (debug version with detailed output)
 pub async fn get_alternative_translations_debug(
     selected_text: String,
     target_language: String,
+    context: Option<String>,
     config: tauri::State<'_, crate::AppState>,
 ) -> Result<serde_json::Value, Error> {
     let mut debug_info = serde_json::Map::new();
@@ -340,15 +1000,11 @@ pub async fn get_alternative_translations_debug(
     let config_clone = config_guard.clone();
     drop(config_guard);
 
-    // Create a special prompt for alternative translations
-    let alternatives_prompt = format!(
-        "Provide up to 5 different word choices or synonyms for the following text when translated into {}. Return ONLY the alternative words/phrases as a JSON array under the key 'alternatives'. Do not include the original word. If no alternatives exist, return an empty array. Do not include any other text or explanation.\n\nText: \"{}\"\n\nRespond in JSON format like this:\n{{\"alternatives\": [\"Alternative 1\", \"Alternative 2\", \"Alternative 3\"]}}",
-        target_language, selected_text
-    );
+    let request = TranslationRequest::alternatives(selected_text.clone(), target_language, context);
 
     debug_info.insert(
         "prompt".to_string(),
-        serde_json::Value::String(alternatives_prompt.clone()),
+        serde_json::Value::String(create_alternatives_prompt(&request)),
     );
     debug_info.insert(
         "api_provider".to_string(),
@@ -359,94 +1015,39 @@ pub async fn get_alternative_translations_debug(
         serde_json::Value::String(config_clone.model.clone()),
     );
 
-    // Create a service with the alternatives prompt
-    let alternatives_service: Box<dyn TranslationProvider + Send + Sync> = if config_clone
-        .api_provider
-        == "azure_translator"
-    {
-        // Azure Translator can't generate alternatives, use fallback
-        if let Some((provider, model)) = config_clone.parse_alternatives_fallback() {
-            log::info!(
-                "Using fallback provider '{}' with model '{}' for alternatives",
-                provider,
-                model
-            );
-            let mut alt_config = config_clone.clone();
-            alt_config.api_provider = provider.clone();
-            alt_config.model = model.clone();
-            alt_config.custom_prompt = alternatives_prompt;
-
-            // For Azure OpenAI, ensure deployment name matches model name
-            if provider == "azure_openai" {
-                log::info!("Setting Azure deployment name to: {}", model);
-                alt_config.azure_deployment_name = model.clone();
-            }
-            alt_config.ensure_azure_deployment_consistency();
+    let using_fallback = uses_alternatives_fallback(
+        &create_provider(config_clone.clone()).capabilities(),
+        &request,
+        &config_clone,
+    );
+    debug_info.insert(
+        "using_fallback".to_string(),
+        serde_json::Value::Bool(using_fallback),
+    );
+    if using_fallback && let Some((provider, model)) = config_clone.parse_alternatives_fallback() {
+        debug_info.insert(
+            "fallback_provider".to_string(),
+            serde_json::Value::String(provider),
+        );
+        debug_info.insert(
+            "fallback_model".to_string(),
+            serde_json::Value::String(model),
+        );
+    }
 
-            debug_info.insert("using_fallback".to_string(), serde_json::Value::Bool(true));
-            debug_info.insert(
-                "fallback_provider".to_string(),
-                serde_json::Value::String(provider.clone()),
-            );
-            debug_info.insert(
-                "fallback_model".to_string(),
-                serde_json::Value::String(model.clone()),
-            );
+    let alternatives_service = match create_alternatives_provider(config_clone, &request) {
+        Ok(service) => service,
+        Err(e) => {
             debug_info.insert(
-                "azure_deployment_name".to_string(),
-                serde_json::Value::String(alt_config.azure_deployment_name.clone()),
+                "error".to_string(),
+                serde_json::Value::String(e.to_string()),
             );
-
-            // Ensure Azure deployment consistency before creating service
-            alt_config.ensure_azure_deployment_consistency();
-
-            match alt_config.api_provider.as_str() {
-                "openai" => Box::new(OpenAITranslationService::new(alt_config)),
-                "azure_openai" => Box::new(AzureOpenAITranslationService::new(alt_config)),
-                "ollama" => Box::new(OllamaTranslationService::new(alt_config)),
-                _ => {
-                    debug_info.insert(
-                        "fallback_error".to_string(),
-                        serde_json::Value::String(
-                            "Unknown provider, defaulting to OpenAI".to_string(),
-                        ),
-                    );
-                    alt_config.api_provider = "openai".to_string();
-                    Box::new(OpenAITranslationService::new(alt_config))
-                }
-            }
-        } else {
-            debug_info.insert("error".to_string(), serde_json::Value::String("Azure Translator cannot generate alternatives. Please configure a fallback provider in settings.".to_string()));
             return Ok(serde_json::Value::Object(debug_info));
         }
-    } else {
-        // Use the current provider for alternatives with custom prompt
-        debug_info.insert("using_fallback".to_string(), serde_json::Value::Bool(false));
-        let mut alt_config = config_clone.clone();
-        alt_config.custom_prompt = alternatives_prompt;
-
-        // Ensure Azure deployment consistency before creating service
-        alt_config.ensure_azure_deployment_consistency();
-
-        match alt_config.api_provider.as_str() {
-            "openai" => Box::new(OpenAITranslationService::new(alt_config)),
-            "azure_openai" => Box::new(AzureOpenAITranslationService::new(alt_config)),
-            "ollama" => Box::new(OllamaTranslationService::new(alt_config)),
-            _ => {
-                debug_info.insert(
-                    "provider_error".to_string(),
-                    serde_json::Value::String(
-                        "Unknown API provider, defaulting to OpenAI".to_string(),
-                    ),
-                );
-                alt_config.api_provider = "openai".to_string();
-                Box::new(OpenAITranslationService::new(alt_config))
-            }
-        }
     };
 
     // Use the service to get alternatives
-    match alternatives_service.translate(&selected_text).await {
+    match alternatives_service.translate(&request).await {
         Ok(result) => {
             let response_text = result.translated_text.trim();
             debug_info.insert(
@@ -535,6 +1136,7 @@ pub async fn get_alternative_translations_debug(
 pub async fn get_alternative_translations(
     selected_text: String,
     target_language: String,
+    context: Option<String>,
     config: tauri::State<'_, crate::AppState>,
 ) -> Result<AlternativeTranslationsResult, Error> {
     log::info!(
@@ -547,83 +1149,11 @@ pub async fn get_alternative_translations(
     let config_clone = config_guard.clone();
     drop(config_guard);
 
-    // Create a special prompt for alternative translations
-    let alternatives_prompt = format!(
-        "Provide up to 5 different word choices or synonyms for the following text when translated into {}. Return ONLY the alternative words/phrases as a JSON array under the key 'alternatives'. Do not include the original word. If no alternatives exist, return an empty array. Do not include any other text or explanation.\n\nText: \"{}\"\n\nRespond in JSON format like this:\n{{\"alternatives\": [\"Alternative 1\", \"Alternative 2\", \"Alternative 3\"]}}",
-        target_language, selected_text
-    );
-
-    // Create a service with the alternatives prompt
-    let alternatives_service: Box<dyn TranslationProvider + Send + Sync> = if config_clone
-        .api_provider
-        == "azure_translator"
-    {
-        // Azure Translator can't generate alternatives, use fallback
-        if let Some((provider, model)) = config_clone.parse_alternatives_fallback() {
-            log::info!(
-                "Using fallback provider '{}' with model '{}' for alternatives",
-                provider,
-                model
-            );
-            let mut alt_config = config_clone.clone();
-            alt_config.api_provider = provider.clone();
-            alt_config.model = model.clone();
-            alt_config.custom_prompt = alternatives_prompt;
-
-            // For Azure OpenAI, ensure deployment name matches model name
-            if provider == "azure_openai" {
-                log::info!("Setting Azure deployment name to: {}", model);
-                alt_config.azure_deployment_name = model.clone();
-            }
-            alt_config.ensure_azure_deployment_consistency();
-
-            match alt_config.api_provider.as_str() {
-                "openai" => Box::new(OpenAITranslationService::new(alt_config)),
-                "azure_openai" => Box::new(AzureOpenAITranslationService::new(alt_config)),
-                "ollama" => Box::new(OllamaTranslationService::new(alt_config)),
-                _ => {
-                    log::warn!(
-                        "Unknown alternatives provider '{}', defaulting to OpenAI",
-                        provider
-                    );
-                    alt_config.api_provider = "openai".to_string();
-                    Box::new(OpenAITranslationService::new(alt_config))
-                }
-            }
-        } else {
-            return Err(Error::ApiError(anyhow::anyhow!(
-                "Azure Translator cannot generate alternatives. Please configure a fallback provider in settings."
-            )));
-        }
-    } else {
-        // Use the current provider for alternatives with custom prompt
-        log::info!(
-            "Using current provider '{}' for alternatives",
-            config_clone.api_provider
-        );
-        let mut alt_config = config_clone.clone();
-        alt_config.custom_prompt = alternatives_prompt;
-
-        // Ensure Azure deployment consistency before creating service
-        alt_config.ensure_azure_deployment_consistency();
-
-        match alt_config.api_provider.as_str() {
-            "openai" => Box::new(OpenAITranslationService::new(alt_config)),
-            "azure_openai" => Box::new(AzureOpenAITranslationService::new(alt_config)),
-            "ollama" => Box::new(OllamaTranslationService::new(alt_config)),
-            _ => {
-                log::warn!(
-                    "Unknown API provider '{}', defaulting to OpenAI",
-                    alt_config.api_provider
-                );
-                alt_config.api_provider = "openai".to_string();
-                Box::new(OpenAITranslationService::new(alt_config))
-            }
-        }
-    };
+    let request = TranslationRequest::alternatives(selected_text.clone(), target_language, context);
+    let alternatives_service = create_alternatives_provider(config_clone, &request)?;
 
     // Use the service to get alternatives
-    match alternatives_service.translate(&selected_text).await {
+    match alternatives_service.translate(&request).await {
         Ok(result) => {
             let response_text = result.translated_text.trim();
             log::info!("Raw AI response for alternatives: '{}'", response_text);
@@ -641,7 +1171,14 @@ pub async fn get_alternative_translations(
 
                 if !alternatives.is_empty() {
                     log::info!("Successfully parsed {} alternatives", alternatives.len());
-                    return Ok(AlternativeTranslationsResult { alternatives });
+                    let details = parsed
+                        .get("details")
+                        .and_then(|d| serde_json::from_value(d.clone()).ok())
+                        .unwrap_or_default();
+                    return Ok(AlternativeTranslationsResult {
+                        alternatives,
+                        details,
+                    });
                 }
             }
 
@@ -678,7 +1215,10 @@ pub async fn get_alternative_translations(
                         "Successfully parsed {} alternatives from fixed JSON",
                         alternatives.len()
                     );
-                    return Ok(AlternativeTranslationsResult { alternatives });
+                    return Ok(AlternativeTranslationsResult {
+                        alternatives,
+                        details: Vec::new(),
+                    });
                 }
             }
 
@@ -716,7 +1256,10 @@ pub async fn get_alternative_translations(
                     "Extracted {} alternatives from text lines",
                     alternatives.len()
                 );
-                Ok(AlternativeTranslationsResult { alternatives })
+                Ok(AlternativeTranslationsResult {
+                    alternatives,
+                    details: Vec::new(),
+                })
             } else {
                 log::warn!("No alternatives found in response");
                 Err(Error::ApiError(anyhow::anyhow!(
@@ -0,0 +1,200 @@
+// Languages come from the backend's catalogue: a built-in list merged with the language
+// lists of the configured translation engines.
+
+import { invoke, listen } from "./tauri";
+
+export interface Language {
+  code: string; // BCP-47 tag, which config and history store
+  script?: string | null; // ISO 15924 script
+  english_name: string;
+  native_name: string;
+  common_names?: string[]; // Alternative names for searching
+  providers?: Record<string, string>; // Code each translation engine uses for it
+}
+
+const AUTO_DETECT: Language = {
+  code: "auto",
+  english_name: "Auto-detect",
+  native_name: "Auto-detect",
+};
+
+let catalogue = $state<Language[]>([AUTO_DETECT]);
+let catalogueListener: Promise<() => void> | null = null;
+
+function setCatalogue(languages: Language[] | null) {
+  if (Array.isArray(languages) && languages.length > 0) {
+    catalogue = [AUTO_DETECT, ...languages];
+  }
+}
+
+/**
+ * Load the language catalogue and keep it current as provider settings change
+ */
+export async function loadLanguageCatalogue(): Promise<void> {
+  catalogueListener ??= listen("language-catalogue-updated", (event) =>
+    setCatalogue(event.payload as Language[]),
+  );
+  try {
+    setCatalogue((await invoke("get_language_catalogue")) as Language[] | null);
+  } catch (error) {
+    console.error("Failed to load language catalogue:", error);
+  }
+}
+
+// Utility functions for language management
+export class LanguageManager {
+  // Find language by code
+  static findByCode(code: string): Language | undefined {
+    return catalogue.find(
+      (lang) => lang.code.toLowerCase() === code.toLowerCase(),
+    );
+  }
+
+  // Find languages by search term (searches english_name, native_name, and common_names)
+  static search(query: string, includeAutoDetect = true): Language[] {
+    if (!query || !query.trim()) {
+      return includeAutoDetect
+        ? catalogue
+        : catalogue.filter((lang) => lang.code !== "auto");
+    }
+
+    const searchTerm = query.toLowerCase().trim();
+    const results = catalogue.filter((lang) => {
+      if (!includeAutoDetect && lang.code === "auto") return false;
+
+      const matches = [
+        lang.english_name.toLowerCase().includes(searchTerm),
+        lang.native_name.toLowerCase().includes(searchTerm),
+        lang.code.toLowerCase().includes(searchTerm),
+        ...(lang.common_names || []).map((name) =>
+          name.toLowerCase().includes(searchTerm),
+        ),
+      ];
+
+      return matches.some(Boolean);
+    });
+
+    // Sort results by relevance (exact matches first, then starts-with, then contains)
+    return results.sort((a, b) => {
+      const getScore = (lang: Language): number => {
+        const names = [
+          lang.english_name,
+          lang.native_name,
+          ...(lang.common_names || []),
+        ];
+        const lowerNames = names.map((name) => name.toLowerCase());
+
+        // Exact match
+        if (lowerNames.some((name) => name === searchTerm)) return 100;
+        // Starts with
+        if (lowerNames.some((name) => name.startsWith(searchTerm))) return 50;
+        // Contains
+        return 10;
+      };
+
+      return getScore(b) - getScore(a);
+    });
+  }
+
+  // Get all languages except auto-detect
+  static getAllLanguages(): Language[] {
+    return catalogue.filter((lang) => lang.code !== "auto");
+  }
+
+  // Get auto-detect language
+  static getAutoDetect(): Language {
+    return AUTO_DETECT;
+  }
+
+  // Format language display name
+  static formatDisplayName(lang: Language, showNative = true): string {
+    if (lang.code === "auto") return lang.english_name;
+    if (!showNative) return lang.english_name;
+    if (lang.english_name === lang.native_name) return lang.english_name;
+    return `${lang.english_name} (${lang.native_name})`;
+  }
+
+  // Create a custom language (for user-defined languages not in the standard list)
+  static createCustomLanguage(name: string): Language {
+    return {
+      code: `custom-${name.toLowerCase().replace(/\s+/g, "-")}`,
+      english_name: name,
+      native_name: name,
+      common_names: [name],
+    };
+  }
+
+  // Resolve a stored language (a tag, or the name of a custom language)
+  static fromStored(value: string): Language {
+    return (
+      this.findByCode(value) ||
+      this.search(value, false).find(
+        (lang) => lang.english_name.toLowerCase() === value.toLowerCase(),
+      ) ||
+      this.createCustomLanguage(value)
+    );
+  }
+
+  // The value config and history store for a language: its tag, or a custom language's name
+  static toStored(lang: Language): string {
+    return this.isCustomLanguage(lang) ? lang.english_name : lang.code;
+  }
+
+  // English name of a stored language, for display
+  static displayName(value: string): string {
+    return this.findByCode(value)?.english_name ?? value;
+  }
+
+  // Check if a language is custom (user-defined)
+  static isCustomLanguage(lang: Language): boolean {
+    return lang.code.startsWith("custom-");
+  }
+
+  // Get language suggestions based on favorites and commonly used languages
+  static getSuggestions(favorites: string[] = []): Language[] {
+    const favoriteLanguages = favorites
+      .map((code) => this.findByCode(code))
+      .filter(Boolean) as Language[];
+
+    const commonLanguages = [
+      "en",
+      "es",
+      "fr",
+      "de",
+      "it",
+      "pt",
+      "ru",
+      "ja",
+      "ko",
+      "zh-Hans",
+      "ar",
+      "hi",
+    ]
+      .map((code) => this.findByCode(code))
+      .filter(Boolean) as Language[];
+
+    // Combine favorites with common languages, remove duplicates
+    const suggestions = [...favoriteLanguages];
+    commonLanguages.forEach((lang) => {
+      if (!suggestions.some((s) => s.code === lang.code)) {
+        suggestions.push(lang);
+      }
+    });
+
+    return suggestions;
+  }
+}
+
+// Type for language selection events
+export interface LanguageSelectionEvent {
+  language: Language;
+  isCustom: boolean;
+}
+
+// Helper function to normalize language names for comparison
+export function normalizeLanguageName(name: string): string {
+  return name
+    .toLowerCase()
+    .trim()
+    .replace(/[^\w\s-]/g, "");
+}
@@ -5,6 +5,7 @@
 
 import * as tauriCore from "@tauri-apps/api/core";
 import * as tauriEvent from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 
 const hasTauri =
   typeof window !== "undefined" &&
@@ -42,3 +43,15 @@ export const listen = (event: string, cb: (...args: any[]) => void) => {
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
   return (tauriEvent.listen as any)(event, cb);
 };
+
+// Listens only to events emitted to this webview window, e.g. the progress of a translation
+// this window started
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+export const listenToWindow = (event: string, cb: (...args: any[]) => void) => {
+  if (!hasTauri) {
+    console.warn("Tauri listen called in browser dev - no-op", event);
+    return Promise.resolve(() => {});
+  }
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  return (getCurrentWebviewWindow().listen as any)(event, cb);
+};
@@ -1,11 +1,47 @@
+import { invoke } from "../tauri";
+
 export interface Config {
   api_provider: string;
   openai_api_key: string;
+  openai_compatible_base_url?: string;
   azure_api_key: string;
   azure_endpoint: string;
   azure_translator_api_key: string;
   azure_translator_endpoint: string;
   ollama_url?: string;
+  anthropic_api_key?: string;
+  gemini_api_key?: string;
+  deepl_api_key?: string;
+  libretranslate_url?: string;
+}
+
+/** What a provider supports, as reported by the backend. */
+export interface ProviderCapabilities {
+  alternatives: boolean;
+  phrase_alternatives: boolean;
+  streaming: boolean;
+  language_detection: boolean;
+  max_input_chars: number | null;
+  max_chunk_chars: number | null;
+  transliteration: boolean;
+  structured_output: boolean;
+  reasoning_effort: boolean;
+}
+
+/**
+ * Ask the backend what a provider supports (the configured provider when omitted)
+ */
+export async function getProviderCapabilities(
+  provider?: string,
+): Promise<ProviderCapabilities | null> {
+  try {
+    return (await invoke("get_provider_capabilities", {
+      provider,
+    })) as ProviderCapabilities | null;
+  } catch (error) {
+    console.error("Failed to load provider capabilities:", error);
+    return null;
+  }
 }
 
 /**
@@ -20,6 +56,11 @@ export function isProviderConfigured(
   switch (provider) {
     case "openai":
       return config.openai_api_key?.length > 0;
+    case "openai_compatible":
+      return !!(
+        config.openai_compatible_base_url &&
+        config.openai_compatible_base_url.length > 0
+      );
     case "azure_openai":
       return (
         config.azure_api_key?.length > 0 && config.azure_endpoint?.length > 0
@@ -33,6 +74,14 @@ export function isProviderConfigured(
       );
     case "ollama":
       return !!(config.ollama_url && config.ollama_url.length > 0);
+    case "anthropic":
+      return !!(config.anthropic_api_key && config.anthropic_api_key.length > 0);
+    case "gemini":
+      return !!(config.gemini_api_key && config.gemini_api_key.length > 0);
+    case "deepl":
+      return !!(config.deepl_api_key && config.deepl_api_key.length > 0);
+    case "libretranslate":
+      return !!(config.libretranslate_url && config.libretranslate_url.length > 0);
     default:
       return false;
   }
@@ -44,7 +93,17 @@ export function isProviderConfigured(
 export function isAnyProviderConfigured(config: Config | null): boolean {
   if (!config) return false;
 
-  const providers = ["openai", "azure_openai", "azure_translator", "ollama"];
+  const providers = [
+    "openai",
+    "openai_compatible",
+    "azure_openai",
+    "azure_translator",
+    "ollama",
+    "anthropic",
+    "gemini",
+    "deepl",
+    "libretranslate",
+  ];
   return providers.some((provider) => isProviderConfigured(config, provider));
 }
 
@@ -54,6 +113,16 @@ export function isAnyProviderConfigured(config: Config | null): boolean {
 export function getConfiguredProviders(config: Config | null): string[] {
   if (!config) return [];
 
-  const providers = ["openai", "azure_openai", "azure_translator", "ollama"];
+  const providers = [
+    "openai",
+    "openai_compatible",
+    "azure_openai",
+    "azure_translator",
+    "ollama",
+    "anthropic",
+    "gemini",
+    "deepl",
+    "libretranslate",
+  ];
   return providers.filter((provider) => isProviderConfigured(config, provider));
 }